@@ -0,0 +1,85 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parser::InteractionTest,
+    runner::{InteractionOutcome, Status},
+};
+
+/// The directory next to the config file ocd keeps its state in
+const CACHE_DIR: &str = ".ocd";
+const LAST_RUN_FILE: &str = "last-run.json";
+
+/// The results of previous runs, keyed by the canonical path of the interaction file
+#[derive(Serialize, Deserialize, Default)]
+pub struct RunCache {
+    #[serde(skip)]
+    path: PathBuf,
+    results: BTreeMap<String, String>,
+}
+
+fn key(file_path: &str) -> String {
+    fs::canonicalize(file_path)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| file_path.to_string())
+}
+
+impl RunCache {
+    /// Loads the cache next to the config file, a missing or broken cache is treated as empty
+    pub fn load(base_dir: &Path) -> Self {
+        let path = base_dir.join(CACHE_DIR).join(LAST_RUN_FILE);
+
+        let mut cache: RunCache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        cache.path = path;
+
+        cache
+    }
+
+    /// Whether no results of a previous run are known, e.g. because ocd never ran here before
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Where the cache is stored
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the interaction did not pass the last time it ran
+    pub fn has_failed(&self, interaction: &InteractionTest) -> bool {
+        self.results
+            .get(&key(&interaction.file_path))
+            .is_some_and(|status| status != Status::Passed.name())
+    }
+
+    pub fn update(&mut self, outcomes: &[InteractionOutcome]) {
+        for outcome in outcomes {
+            self.results.insert(
+                key(&outcome.interaction.file_path),
+                outcome.status().name().to_string(),
+            );
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let error = |e: std::io::Error| format!("could not write '{}': {}", self.path.display(), e);
+
+        let dir = self.path.parent().unwrap();
+        if !dir.exists() {
+            fs::create_dir_all(dir).map_err(error)?;
+            // the cache should never end up in version control
+            fs::write(dir.join(".gitignore"), "*\n").map_err(error)?;
+        }
+
+        let content = serde_json::to_string_pretty(self).unwrap();
+        fs::write(&self.path, content).map_err(error)
+    }
+}
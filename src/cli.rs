@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::report::ReportTarget;
+
+#[derive(Parser)]
+#[command(version, about = "An offline code tester for interactive command line programs")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+
+    /// Path to the config file
+    #[arg(short, long, global = true, default_value = "ocd.toml")]
+    pub config: PathBuf,
+
+    /// The amount of interactions to run in parallel (overrides `runner.thread_count`)
+    #[arg(short = 'j', long, global = true)]
+    pub threads: Option<u64>,
+
+    /// How long (in milliseconds) to wait for the program to respond (overrides `runner.timeout`)
+    #[arg(short, long, global = true)]
+    pub timeout: Option<u64>,
+
+    /// How long (in milliseconds) an interaction may run in total before the program is killed
+    /// (overrides `runner.total_timeout`)
+    #[arg(short = 'T', long, global = true)]
+    pub total_timeout: Option<u64>,
+
+    /// Run failing interactions to the end and show a diff of the whole conversation (overrides
+    /// `runner.diff`)
+    #[arg(short, long, global = true)]
+    pub diff: bool,
+
+    /// Run failing interactions to the end and report every mismatching line (overrides
+    /// `runner.collect_all`)
+    #[arg(short = 'a', long, global = true)]
+    pub collect_all: bool,
+
+    /// Stop after the first interaction that did not pass (same as `--max-failures 1`)
+    #[arg(long, global = true, conflicts_with = "max_failures")]
+    pub fail_fast: bool,
+
+    /// Stop once this many interactions did not pass, killing the running ones (overrides
+    /// `runner.max_failures`)
+    #[arg(long, value_name = "N", global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_failures: Option<u64>,
+
+    /// How many lines of output before a failure are shown (overrides `output.context_lines`)
+    #[arg(short = 'C', long = "context", value_name = "LINES", global = true)]
+    pub context_lines: Option<usize>,
+
+    /// How many of the slowest interactions are listed in the summary (overrides `output.slowest`)
+    #[arg(long, value_name = "N", global = true)]
+    pub slowest: Option<usize>,
+
+    /// Write the whole conversation of every failing interaction to this directory (overrides
+    /// `output.artifacts`)
+    #[arg(long, value_name = "DIR", global = true)]
+    pub artifacts: Option<PathBuf>,
+
+    /// Only use interactions whose name contains this string. Can be used multiple times
+    #[arg(short, long, value_name = "NAME", global = true)]
+    pub filter: Vec<String>,
+
+    /// Only use interactions whose file path matches this glob, e.g. `tests/basic/*`. Can be used
+    /// multiple times
+    #[arg(short, long = "path", value_name = "GLOB", global = true)]
+    pub paths: Vec<String>,
+
+    /// Only use interactions with this tag (set with `$tag <tags>`). Can be used multiple times
+    #[arg(long = "tag", value_name = "TAG", global = true)]
+    pub tags: Vec<String>,
+
+    /// Skip interactions whose name contains this string, whose file path matches this glob or
+    /// that have this tag. Can be used multiple times
+    #[arg(short = 'x', long = "exclude", value_name = "PATTERN", global = true)]
+    pub excludes: Vec<String>,
+
+    /// Only run the interactions that did not pass the last time they ran
+    #[arg(long, global = true, conflicts_with = "failed_first")]
+    pub failed: bool,
+
+    /// Run the interactions that did not pass the last time they ran before all others
+    #[arg(long, global = true)]
+    pub failed_first: bool,
+
+    /// The format of the output on stdout
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// When to use colors. `auto` disables them if stdout is not a terminal or `NO_COLOR` is set
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Show the progress with plain characters (`.`, `F`, `E`) instead of emoji (overrides
+    /// `output.ascii`)
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Write a report of the run to a file, e.g. `junit=report.xml`. Can be used multiple times
+    #[arg(long = "report", value_name = "FORMAT=FILE", global = true)]
+    pub reports: Vec<ReportTarget>,
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub enum CliCommand {
+    /// Run all interactions (default)
+    Run,
+    /// Run all interactions and rerun them whenever the program or an interaction file changes
+    Watch,
+    /// List all interactions without running them
+    List,
+    /// Check the config and all interaction files without running them
+    Check,
+    /// Create a new config file
+    Init {
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A progress bar and human readable failures
+    Text,
+    /// One JSON object per line for every started and finished interaction
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::{
+    cli::Cli,
+    filter::InteractionFilter,
+    parser::{self, InteractionParseError, InteractionTest},
+};
+
+/// Parses all interaction files in `path`.
+///
+/// Files that can not be parsed do not abort the collection, their errors are returned next to
+/// the parsed interactions.
+pub fn collect_interactions(
+    cli: &Cli,
+    path: &Path,
+    file_patterns: &Option<Vec<String>>,
+) -> Result<(Vec<InteractionTest>, Vec<InteractionParseError>), String> {
+    if !path.exists() {
+        return Err(format!("interaction path '{}' does not exist", path.display()));
+    }
+
+    let file_regex = interaction_file_regex(file_patterns)?;
+
+    let filter = InteractionFilter::new(cli)?;
+
+    // directories that can not be read are reported like files that can not be parsed
+    let mut read_errors = Vec::new();
+    let file_paths = collect_files(path, &file_regex, &mut read_errors);
+
+    let (interactions, mut parse_errors) = parse_interactions(&filter, file_paths);
+    read_errors.append(&mut parse_errors);
+
+    Ok((interactions, read_errors))
+}
+
+/// The regex a file name needs to fully match to be considered an interaction file
+pub fn interaction_file_regex(file_patterns: &Option<Vec<String>>) -> Result<Regex, String> {
+    let file_pattern = match file_patterns {
+        Some(patterns) => patterns.join("|"),
+        None => ".*\\.txt".to_string(),
+    };
+
+    Regex::new(&format!("^({})$", file_pattern))
+        .map_err(|e| format!("invalid interaction pattern: {}", e))
+}
+
+/// Parses the given interaction files and applies the filters given on the command line
+pub fn parse_interactions(
+    filter: &InteractionFilter,
+    file_paths: Vec<String>,
+) -> (Vec<InteractionTest>, Vec<InteractionParseError>) {
+    let mut interactions = Vec::new();
+    let mut parse_errors = Vec::new();
+
+    for file_path in file_paths {
+        if !filter.matches_path(&file_path) {
+            continue;
+        }
+
+        let interaction = match parser::parse(&file_path) {
+            Ok(interaction) => interaction,
+            Err(e) => {
+                parse_errors.push(e);
+                continue;
+            }
+        };
+
+        if filter.matches(&interaction) {
+            interactions.push(interaction);
+        }
+    }
+
+    (interactions, parse_errors)
+}
+
+fn collect_files(
+    path: &Path,
+    file_regex: &Regex,
+    errors: &mut Vec<InteractionParseError>,
+) -> Vec<String> {
+    if path.is_file() {
+        let file_name = path.file_name().unwrap().to_string_lossy();
+
+        if !file_regex.is_match(&file_name) {
+            return vec![];
+        }
+
+        // the interaction is identified by its path, a lossy one might not even exist
+        let Some(file_path) = path.to_str() else {
+            let error = std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "the file name is not valid UTF-8",
+            );
+            errors.push(InteractionParseError::io(&path.to_string_lossy(), error));
+            return vec![];
+        };
+
+        return vec![file_path.to_string()];
+    }
+
+    let mut interactions = vec![];
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(InteractionParseError::io(&path.to_string_lossy(), e));
+            return interactions;
+        }
+    };
+
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(e) => {
+                errors.push(InteractionParseError::io(&path.to_string_lossy(), e));
+                continue;
+            }
+        };
+        interactions.append(&mut collect_files(&path, file_regex, errors));
+    }
+
+    interactions
+}
@@ -1,11 +1,59 @@
+use std::{
+    collections::BTreeMap,
+    env::{self, current_dir},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
 use serde::Deserialize;
 
+pub const CONFIG_TEMPLATE: &str = r#"class_path = "out/production/project" # For an intellij project this should be "out/production/<your-project-name>"
+main_class = "path.to.your.Main"
+
+# Instead of class_path and main_class any other program can be tested
+# [program]
+# command = "python3" # The command to run. Paths containing a '/' are relative to this file
+# args = [ "-u", "main.py" ] # Optional
+# env = { KEY = "value" } # Additional environment variables. Optional
+# working_dir = "." # Relative to this file. Defaults to the directory of each interaction file
+
+[interaction]
+path = "tests" # Path to the folder containing your interactions
+pattern = [ ".*\\.txt" ] # An array of Regex patterns a filename needs to fully match to be considered an interaction file. Optional
+
+[runner] # Optional
+# thread_count = 4 # The amount of interactions to run in parallel, 0 runs them one after another. Optional, defaults to the number of CPUs
+timeout = 1000 # How long (in milliseconds) ocd should wait for your java programm to respond. Optional
+total_timeout = 10000 # How long (in milliseconds) an interaction may run in total before your programm is killed. Optional
+diff = false # Run failing interactions to the end and show a diff of the whole conversation. Optional
+collect_all = false # Run failing interactions to the end and report every mismatching line. Optional
+# ignored_crashes = [ 'java\.util\.NoSuchElementException' ] # Regex patterns for stderr of crashes that are treated as a normal exit. Optional, defaults to the Scanner exception of a java program waiting for more input
+# max_failures = 1 # Stop the run once this many interactions did not pass. Optional, runs all interactions by default
+
+[output] # Optional
+context_lines = 10 # How many lines of output before a failure are shown. Optional
+# ascii = false # Show the progress with plain characters instead of emoji. Optional, defaults to true if the output is not a terminal
+slowest = 5 # How many of the slowest interactions are listed in the summary. Optional
+# artifacts = "target/ocd" # Directory the whole conversation of every failing interaction is written to. Optional
+"#;
+
 #[derive(Deserialize)]
 pub struct OcdConfig {
-    pub class_path: String,
-    pub main_class: String,
+    pub class_path: Option<String>,
+    pub main_class: Option<String>,
+    pub program: Option<OcdProgramConfig>,
     pub interaction: OcdInteractionConfig,
     pub runner: Option<OcdRunnerConfig>,
+    pub output: Option<OcdOutputConfig>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct OcdProgramConfig {
+    pub command: String,
+    pub args: Option<Vec<String>>,
+    pub env: Option<BTreeMap<String, String>>,
+    pub working_dir: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -18,4 +66,126 @@ pub struct OcdInteractionConfig {
 pub struct OcdRunnerConfig {
     pub thread_count: Option<u64>,
     pub timeout: Option<u64>,
+    pub total_timeout: Option<u64>,
+    pub diff: Option<bool>,
+    pub collect_all: Option<bool>,
+    pub ignored_crashes: Option<Vec<String>>,
+    pub max_failures: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct OcdOutputConfig {
+    pub context_lines: Option<usize>,
+    pub ascii: Option<bool>,
+    pub slowest: Option<usize>,
+    pub artifacts: Option<String>,
+}
+
+impl OcdConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file_str = match fs::read_to_string(path) {
+            Ok(file_str) => file_str,
+            Err(_) if !path.exists() => return Err(format!("could not find '{}'", path.display())),
+            Err(_) => {
+                return Err(format!(
+                    "'{}' was found but could not be read",
+                    path.display()
+                ))
+            }
+        };
+
+        toml::from_str(&file_str).map_err(|e| e.to_string())
+    }
+
+    /// The patterns of crashes that are treated as a normal exit, by default the crash of a java
+    /// program reading from a `Scanner` after the last input when using the java preset
+    pub fn ignored_crashes(&self) -> Result<Vec<Regex>, String> {
+        let configured = self.runner.as_ref().and_then(|r| r.ignored_crashes.clone());
+
+        let patterns = match configured {
+            Some(patterns) => patterns,
+            None if self.program.is_none() => vec![JAVA_SCANNER_CRASH.to_string()],
+            None => Vec::new(),
+        };
+
+        patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| format!("invalid ignored crash pattern '{}': {}", pattern, e))
+            })
+            .collect()
+    }
+
+    /// The program to test, either from the `[program]` section or from the java preset
+    /// (`class_path` and `main_class`).
+    ///
+    /// Relative paths are resolved against `base_dir`, the directory of the config file.
+    pub fn program(&self, base_dir: &Path) -> Result<OcdProgramConfig, String> {
+        match (&self.program, &self.class_path, &self.main_class) {
+            (Some(program), None, None) => {
+                let mut program = program.clone();
+                if program.command.contains(std::path::is_separator) {
+                    program.command = absolute(base_dir, &program.command)
+                        .to_string_lossy()
+                        .to_string();
+                }
+                program.working_dir = program
+                    .working_dir
+                    .map(|dir| absolute(base_dir, &dir).to_string_lossy().to_string());
+                Ok(program)
+            }
+            (None, Some(class_path), Some(main_class)) => Ok(OcdProgramConfig {
+                command: "java".to_string(),
+                args: Some(vec![
+                    "-classpath".to_string(),
+                    absolute(base_dir, class_path).to_string_lossy().to_string(),
+                    main_class.to_string(),
+                ]),
+                env: None,
+                working_dir: None,
+            }),
+            (Some(_), _, _) => Err(
+                "'[program]' can not be combined with 'class_path' and 'main_class'".to_string(),
+            ),
+            (None, None, None) => Err(
+                "either '[program]' or 'class_path' and 'main_class' need to be defined"
+                    .to_string(),
+            ),
+            (None, _, _) => {
+                Err("'class_path' and 'main_class' need to be defined together".to_string())
+            }
+        }
+    }
+}
+
+impl OcdProgramConfig {
+    /// Checks that the command is an existing file or can be found in `PATH`
+    pub fn check_command(&self) -> Result<(), String> {
+        let command = Path::new(&self.command);
+
+        let found = if self.command.contains(std::path::is_separator) {
+            command.is_file()
+        } else {
+            env::var_os("PATH").is_some_and(|paths| {
+                env::split_paths(&paths).any(|dir| {
+                    let path = dir.join(command);
+                    path.is_file() || (cfg!(windows) && path.with_extension("exe").is_file())
+                })
+            })
+        };
+
+        if found {
+            Ok(())
+        } else {
+            Err(format!("could not find the command '{}'", self.command))
+        }
+    }
+}
+
+/// The crash of a java program that reads from a `Scanner` after all input was written
+pub const JAVA_SCANNER_CRASH: &str = r"(?s)java\.util\.NoSuchElementException.*java\.util\.Scanner";
+
+fn absolute(base_dir: &Path, path: &str) -> PathBuf {
+    current_dir().unwrap().join(base_dir).join(path)
 }
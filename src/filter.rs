@@ -0,0 +1,73 @@
+use glob::Pattern;
+
+use crate::{cli::Cli, parser::InteractionTest};
+
+/// Selects the interactions to use based on the filters given on the command line
+pub struct InteractionFilter {
+    names: Vec<String>,
+    paths: Vec<Pattern>,
+    tags: Vec<String>,
+    excludes: Vec<String>,
+}
+
+impl InteractionFilter {
+    pub fn new(cli: &Cli) -> Result<Self, String> {
+        let paths = cli
+            .paths
+            .iter()
+            .map(|path| {
+                Pattern::new(path).map_err(|e| format!("invalid path pattern '{}': {}", path, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            names: cli.filter.clone(),
+            paths,
+            tags: cli.tags.clone(),
+            excludes: cli.excludes.clone(),
+        })
+    }
+
+    /// Whether the interaction passes all filters.
+    ///
+    /// Each kind of filter matches if any of its values matches, an interaction needs to match
+    /// every kind that was given and none of the excludes.
+    pub fn matches(&self, interaction: &InteractionTest) -> bool {
+        let name_matches = self.names.is_empty()
+            || self
+                .names
+                .iter()
+                .any(|name| interaction.name.contains(name.as_str()));
+
+        let tag_matches =
+            self.tags.is_empty() || self.tags.iter().any(|tag| interaction.tags.contains(tag));
+
+        name_matches
+            && tag_matches
+            && self.matches_path(&interaction.file_path)
+            && !self.is_excluded(interaction)
+    }
+
+    /// Whether the file passes the path filters and is not excluded by a glob. Unlike the other
+    /// filters this does not need the parsed file, so files outside the selection are never parsed
+    pub fn matches_path(&self, file_path: &str) -> bool {
+        let path_matches = self.paths.is_empty()
+            || self.paths.iter().any(|pattern| pattern.matches(file_path));
+
+        let path_excluded = self.excludes.iter().any(|exclude| {
+            Pattern::new(exclude)
+                .map(|pattern| pattern.matches(file_path))
+                .unwrap_or(false)
+        });
+
+        path_matches && !path_excluded
+    }
+
+    /// An exclude matches if it is contained in the name or is a tag, globs are checked by
+    /// `matches_path`
+    fn is_excluded(&self, interaction: &InteractionTest) -> bool {
+        self.excludes.iter().any(|exclude| {
+            interaction.name.contains(exclude.as_str()) || interaction.tags.contains(exclude)
+        })
+    }
+}
@@ -1,119 +1,378 @@
+mod cache;
+mod cli;
+mod collect;
 mod config;
+mod filter;
 mod parser;
+mod report;
 mod runner;
+mod style;
+mod watch;
 
-use std::{env::current_dir, fs, io::Read, path::Path, time::Duration};
+use std::{
+    fs,
+    env,
+    io::{stdout, IsTerminal},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
 
-use parser::InteractionTest;
-use regex::Regex;
+use clap::Parser;
+use cli::{Cli, CliCommand, ColorChoice, OutputFormat};
+use cache::RunCache;
+use collect::collect_interactions;
+use parser::{InteractionParseError, InteractionTest};
 
-use crate::{config::OcdConfig, runner::RunnerConfig};
+use crate::{
+    config::{OcdConfig, CONFIG_TEMPLATE},
+    report::{ArtifactReporter, JsonReporter, ProgressReporter, Reporter, Summary},
+    runner::{RunnerConfig, RunnerError},
+};
 
-fn main() {
-    let config_file_path = "ocd.toml";
-    let file = fs::File::open(config_file_path);
-    if let Err(_) = file {
-        println!("could not find '{}'", config_file_path);
-        return;
-    }
-    let mut file = file.unwrap();
+mod exit_code {
+    pub const SUCCESS: u8 = 0;
+    /// At least one interaction produced unexpected output
+    pub const FAILED: u8 = 1;
+    /// At least one interaction crashed the program or timed out
+    pub const ERRORED: u8 = 2;
+    /// The config, the command line or an interaction file is invalid
+    pub const CONFIG_ERROR: u8 = 3;
+    /// The run was cancelled with Ctrl+C, SIGTERM or SIGHUP
+    pub const CANCELLED: u8 = 130;
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return ExitCode::from(if e.use_stderr() {
+                exit_code::CONFIG_ERROR
+            } else {
+                exit_code::SUCCESS
+            });
+        }
+    };
 
-    let mut file_str = String::new();
-    if let Err(_) = file.read_to_string(&mut file_str) {
-        println!("'{}' was found but could not be read", config_file_path);
+    style::set_colors(match cli.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        }
+    });
+
+    // the programs run in their own process groups and do not receive Ctrl+C, ocd kills them. The
+    // same happens on SIGTERM and SIGHUP, so no program is left running when ocd is terminated
+    let handler = ctrlc::set_handler(|| {
+        if !runner::cancel() {
+            std::process::exit(exit_code::CANCELLED.into());
+        }
+    });
+    if let Err(e) = handler {
+        eprintln!("could not handle termination signals: {}", e);
     }
 
-    let config = toml::from_str::<OcdConfig>(file_str.as_str());
+    let result = match cli.command.unwrap_or(CliCommand::Run) {
+        CliCommand::Run => run(&cli),
+        CliCommand::Watch => watch::watch(&cli),
+        CliCommand::List => list(&cli),
+        CliCommand::Check => check(&cli),
+        CliCommand::Init { force } => init(&cli.config, force),
+    };
 
-    if let Err(e) = config {
-        println!("{}", e);
-        return;
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(exit_code::CONFIG_ERROR)
+        }
     }
+}
 
-    let config = config.unwrap();
+fn run(cli: &Cli) -> Result<u8, String> {
+    let config = load_config(cli)?;
+    let interaction_path = base_dir(&cli.config).join(&config.interaction.path);
+    let (mut interactions, parse_errors) =
+        collect_interactions(cli, &interaction_path, &config.interaction.pattern)?;
 
-    let class_path = config.class_path;
-    let main_path = config.main_class;
-    let interactions_dir = config.interaction.path;
-    let interaction_file_patterns = config
-        .interaction
-        .pattern
-        .unwrap_or_else(|| vec![".*\\.txt".to_string()]);
-    let runner_config = config.runner.unwrap_or_default();
-    let threads = runner_config.thread_count.unwrap_or(4);
-    let timeout = runner_config.timeout.unwrap_or(1000);
+    if cli.failed || cli.failed_first {
+        let cache = RunCache::load(&base_dir(&cli.config));
 
-    let path = Path::new(&class_path);
+        if cli.failed && cache.is_empty() {
+            // running nothing would look like every interaction passed
+            eprintln!(
+                "no results of a previous run in '{}', running all interactions",
+                cache.path().display()
+            );
+        } else if cli.failed {
+            interactions.retain(|interaction| cache.has_failed(interaction));
+        } else {
+            interactions.sort_by_key(|interaction| !cache.has_failed(interaction));
+        }
+    }
 
-    let absolute_path = if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        current_dir().unwrap().join(path)
-    };
+    run_interactions(cli, &config, interactions, parse_errors)
+}
 
-    let class_path = absolute_path.to_str().unwrap().to_string();
+/// Runs the interactions with all reporters requested on the command line and returns the exit
+/// code for the outcome
+fn run_interactions(
+    cli: &Cli,
+    config: &OcdConfig,
+    interactions: Vec<InteractionTest>,
+    parse_errors: Vec<InteractionParseError>,
+) -> Result<u8, String> {
+    let program = config.program(&base_dir(&cli.config))?;
+    program.check_command()?;
+    let runner_config = config.runner.as_ref();
+    let threads = runner_config
+        .and_then(|r| r.thread_count)
+        .unwrap_or_else(|| thread::available_parallelism().map_or(4, |n| n.get() as u64));
+    let timeout = runner_config.and_then(|r| r.timeout).unwrap_or(1000);
+    let total_timeout = runner_config.and_then(|r| r.total_timeout).unwrap_or(10000);
 
-    let config = RunnerConfig {
-        command: "java".to_string(),
-        arguments: vec!["-classpath".to_string(), class_path, main_path.to_string()],
+    let runner_config = RunnerConfig {
+        command: program.command,
+        arguments: program.args.unwrap_or_default(),
+        env: program.env.unwrap_or_default().into_iter().collect(),
+        working_dir: program.working_dir.map(PathBuf::from),
         timeout: Duration::from_millis(timeout),
+        total_timeout: Duration::from_millis(total_timeout),
+        diff: runner_config.and_then(|r| r.diff).unwrap_or(false),
+        collect_all: runner_config.and_then(|r| r.collect_all).unwrap_or(false),
+        ignored_crashes: config.ignored_crashes()?,
+        max_failures: runner_config.and_then(|r| r.max_failures),
+    };
+
+    let mut reporters: Vec<Box<dyn Reporter>> = Vec::new();
+
+    match cli.format {
+        OutputFormat::Text => {
+            println!(
+                "Found {} interactions in '{}'",
+                interactions.len(),
+                config.interaction.path
+            );
+            let output = config.output.as_ref();
+            let context_lines = output.and_then(|o| o.context_lines);
+            let ascii = output.and_then(|o| o.ascii);
+            reporters.push(Box::new(ProgressReporter::new(
+                threads == 0,
+                context_lines.unwrap_or(10),
+                // emoji are only shown in terminals, logs and pipes get plain characters
+                ascii.unwrap_or(!stdout().is_terminal()),
+                stdout().is_terminal(),
+            )));
+        }
+        OutputFormat::Json => reporters.push(Box::new(JsonReporter::new(Box::new(stdout())))),
+    }
+
+    for report in &cli.reports {
+        reporters.push(report.reporter()?);
+    }
+
+    // paths on the command line are relative to the current directory, not to the config file
+    let artifacts = match (&cli.artifacts, config.output.as_ref()) {
+        (Some(dir), _) => Some(dir.to_path_buf()),
+        (None, Some(output)) => output
+            .artifacts
+            .as_ref()
+            .map(|dir| base_dir(&cli.config).join(dir)),
+        (None, None) => None,
     };
+    if let Some(dir) = artifacts {
+        reporters.push(Box::new(ArtifactReporter::new(dir)?));
+    }
 
-    let interaction_path: &str = interactions_dir.as_str();
+    for error in &parse_errors {
+        reporters.parse_failed(error);
+    }
 
-    let interactions = collect_interactions(interaction_path, &interaction_file_patterns);
+    let start = Instant::now();
+    let outcomes = runner::run(interactions, runner_config, threads, &mut reporters);
+    let slowest = config.output.as_ref().and_then(|o| o.slowest).unwrap_or(5);
 
-    println!(
-        "Found {} interactions in '{}'",
-        interactions.len(),
-        interaction_path
-    );
+    reporters.finish(&Summary::new(&outcomes, start.elapsed(), slowest))?;
+
+    let mut cache = RunCache::load(&base_dir(&cli.config));
+    cache.update(&outcomes);
+    if let Err(e) = cache.save() {
+        eprintln!("{}", e);
+    }
 
-    let fails = runner::run(interactions, config, threads);
+    let mut fails = outcomes
+        .iter()
+        .filter_map(|outcome| outcome.result.as_ref().err())
+        .peekable();
+
+    if runner::is_cancelled() {
+        return Ok(exit_code::CANCELLED);
+    }
+
+    if !parse_errors.is_empty() {
+        return Ok(exit_code::CONFIG_ERROR);
+    }
+
+    if fails.peek().is_none() {
+        return Ok(exit_code::SUCCESS);
+    }
 
-    if fails.is_empty() {
-        println!("All interactions passed 🎉");
+    if fails.any(|fail| matches!(
+            fail,
+            RunnerError::Error { .. }
+                | RunnerError::Start { .. }
+                | RunnerError::Crash { .. }
+                | RunnerError::Timeout { .. }
+        )) {
+        Ok(exit_code::ERRORED)
     } else {
-        for fail in fails {
-            println!("{}", fail.to_string());
-        }
+        Ok(exit_code::FAILED)
     }
 }
 
-fn collect_interactions(path_name: &str, file_patterns: &Vec<String>) -> Vec<InteractionTest> {
-    let file_pattern = format!("^({})$", file_patterns.join("|"));
-    let file_regex = Regex::new(&file_pattern).expect("invalid regex");
-    let interactions = collect_files(path_name, &file_regex)
-        .into_iter()
-        .map(|path| parser::parse(&path).expect("failed to parse interaction"));
+fn list(cli: &Cli) -> Result<u8, String> {
+    let config = load_config(cli)?;
+    let interaction_path = base_dir(&cli.config).join(&config.interaction.path);
+    let (interactions, parse_errors) =
+        collect_interactions(cli, &interaction_path, &config.interaction.pattern)?;
 
-    interactions.collect()
+    for interaction in &interactions {
+        if interaction.tags.is_empty() {
+            println!("{} ({})", interaction.name, interaction.file_path);
+        } else {
+            println!(
+                "{} ({}) [{}]",
+                interaction.name,
+                interaction.file_path,
+                interaction.tags.join(", ")
+            );
+        }
+    }
+    println!("{} interactions", interactions.len());
+
+    for error in &parse_errors {
+        eprintln!("{}", error.render());
+    }
+
+    if parse_errors.is_empty() {
+        Ok(exit_code::SUCCESS)
+    } else {
+        Ok(exit_code::CONFIG_ERROR)
+    }
 }
 
-fn collect_files(path_name: &str, file_regex: &Regex) -> Vec<String> {
-    let path = Path::new(path_name);
+fn check(cli: &Cli) -> Result<u8, String> {
+    let config = load_config(cli)?;
+    let base_dir = base_dir(&cli.config);
 
-    if path.is_file() {
-        let file_name = path.file_name().unwrap().to_str().unwrap();
+    config.program(&base_dir)?.check_command()?;
+    config.ignored_crashes()?;
 
-        if file_regex.is_match(file_name) {
-            return vec![path.to_string_lossy().to_string()];
+    if let Some(class_path) = &config.class_path {
+        let class_path = base_dir.join(class_path);
+        if !class_path.is_dir() {
+            return Err(format!(
+                "class path '{}' does not exist",
+                class_path.display()
+            ));
         }
+    }
 
-        return vec![];
+    if let Some(working_dir) = config.program.as_ref().and_then(|p| p.working_dir.as_ref()) {
+        let working_dir = base_dir.join(working_dir);
+        if !working_dir.is_dir() {
+            return Err(format!(
+                "working directory '{}' does not exist",
+                working_dir.display()
+            ));
+        }
     }
 
-    let mut interactions = vec![];
-    let entries = std::fs::read_dir(path).unwrap();
+    let interaction_path = base_dir.join(&config.interaction.path);
+    let (interactions, parse_errors) =
+        collect_interactions(cli, &interaction_path, &config.interaction.pattern)?;
 
-    for entry in entries {
-        let path = entry.unwrap().path();
-        if path.is_dir() {
-            interactions.append(&mut collect_files(path.to_str().unwrap(), file_regex));
-        } else if path.is_file() {
-            interactions.append(&mut collect_files(path.to_str().unwrap(), file_regex));
+    if !parse_errors.is_empty() {
+        for error in &parse_errors {
+            println!("{}", error.render());
         }
+
+        return Err(format!(
+            "{} of {} interaction files could not be parsed",
+            parse_errors.len(),
+            interactions.len() + parse_errors.len()
+        ));
+    }
+
+    println!(
+        "'{}' is valid, found {} interactions",
+        cli.config.display(),
+        interactions.len()
+    );
+
+    Ok(exit_code::SUCCESS)
+}
+
+fn init(config_path: &Path, force: bool) -> Result<u8, String> {
+    if config_path.exists() && !force {
+        return Err(format!(
+            "'{}' already exists, use --force to overwrite it",
+            config_path.display()
+        ));
     }
 
-    interactions
+    fs::write(config_path, CONFIG_TEMPLATE)
+        .map_err(|e| format!("could not write '{}': {}", config_path.display(), e))?;
+    println!("Created '{}'", config_path.display());
+
+    Ok(exit_code::SUCCESS)
+}
+
+/// Loads the config file and applies the overrides given on the command line
+fn load_config(cli: &Cli) -> Result<OcdConfig, String> {
+    let mut config = OcdConfig::load(&cli.config)?;
+
+    let runner = config.runner.get_or_insert_with(Default::default);
+    if let Some(threads) = cli.threads {
+        runner.thread_count = Some(threads);
+    }
+    if let Some(timeout) = cli.timeout {
+        runner.timeout = Some(timeout);
+    }
+    if let Some(total_timeout) = cli.total_timeout {
+        runner.total_timeout = Some(total_timeout);
+    }
+    if cli.diff {
+        runner.diff = Some(true);
+    }
+    if cli.collect_all {
+        runner.collect_all = Some(true);
+    }
+    if cli.fail_fast {
+        runner.max_failures = Some(1);
+    }
+    if let Some(max_failures) = cli.max_failures {
+        runner.max_failures = Some(max_failures as usize);
+    }
+
+    let output = config.output.get_or_insert_with(Default::default);
+    if let Some(context_lines) = cli.context_lines {
+        output.context_lines = Some(context_lines);
+    }
+    if cli.ascii {
+        output.ascii = Some(true);
+    }
+    if let Some(slowest) = cli.slowest {
+        output.slowest = Some(slowest);
+    }
+
+    Ok(config)
+}
+
+/// The directory relative paths in the config file are resolved against
+fn base_dir(config_path: &Path) -> PathBuf {
+    config_path.parent().unwrap_or(Path::new("")).to_path_buf()
 }
@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use crate::style::{style_blue, style_bold, style_red};
+
+use super::DIRECTIVES;
+
+#[derive(Debug, Clone)]
+pub enum ParseErrorKind {
+  /// The file could not be read
+  Io(String),
+  /// A line starting with `<` that is not followed by `l`, `r`, `e` or `E`
+  UnknownOutputPrefix(Option<char>),
+  /// A line starting with `>` that is not followed by a space
+  MissingInputSpace,
+  /// A line starting with `$` that is not followed by a second `$`
+  SingleDollar,
+  /// A `$<name>` line with an unknown name
+  UnknownDirective(String),
+  /// A `>` line after the `$eof` in the given line
+  InputAfterEof(usize),
+  /// A `$exit` line whose argument is not a number
+  InvalidExitCode(String),
+  /// A `<r` or `<E` line whose content is not a valid regex
+  InvalidRegex(regex::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct InteractionParseError {
+  pub file_path: String,
+  /// 1-based, 0 if the error is not caused by a specific line
+  pub line: usize,
+  /// 1-based, 0 if the error is not caused by a specific line
+  pub column: usize,
+  /// The amount of characters the error spans, starting at `column`
+  pub length: usize,
+  /// The content of the line containing the error
+  pub source: String,
+  pub kind: ParseErrorKind,
+}
+
+impl InteractionParseError {
+  pub fn io(file_path: &str, error: std::io::Error) -> Self {
+    Self {
+      file_path: file_path.to_string(),
+      line: 0,
+      column: 0,
+      length: 0,
+      source: String::new(),
+      kind: ParseErrorKind::Io(error.to_string()),
+    }
+  }
+
+  pub fn message(&self) -> String {
+    match &self.kind {
+      ParseErrorKind::Io(error) => format!("could not read file: {}", error),
+      ParseErrorKind::UnknownOutputPrefix(Some(c)) => format!("unknown output kind `<{}`", c),
+      ParseErrorKind::UnknownOutputPrefix(None) => "missing output kind after `<`".to_string(),
+      ParseErrorKind::MissingInputSpace => "missing space after `>`".to_string(),
+      ParseErrorKind::SingleDollar => "`$` without second `$`".to_string(),
+      ParseErrorKind::UnknownDirective(name) => format!("unknown directive `${}`", name),
+      ParseErrorKind::InputAfterEof(_) => "input after stdin was closed".to_string(),
+      ParseErrorKind::InvalidExitCode(code) if code.trim().is_empty() => {
+        "missing exit code after `$exit`".to_string()
+      }
+      ParseErrorKind::InvalidExitCode(code) => format!("invalid exit code `{}`", code.trim()),
+      ParseErrorKind::InvalidRegex(regex::Error::Syntax(error)) => {
+        // the syntax error contains its own snippet, only the last line describes the error
+        let description = error.lines().last().unwrap_or_default();
+        format!("invalid regex: {}", description.trim_start_matches("error: "))
+      }
+      ParseErrorKind::InvalidRegex(error) => format!("invalid regex: {}", error),
+    }
+  }
+
+  /// A hint on how to fix the error, if there is an obvious one
+  pub fn suggestion(&self) -> Option<String> {
+    let rest = self.source.chars().skip(1).collect::<String>();
+
+    match &self.kind {
+      ParseErrorKind::Io(_) | ParseErrorKind::InvalidRegex(_) => None,
+      ParseErrorKind::InputAfterEof(line) => {
+        Some(format!("stdin is closed by the `$eof` in line {}", line))
+      }
+      ParseErrorKind::InvalidExitCode(_) => Some("use a number like `$exit 0`".to_string()),
+      ParseErrorKind::UnknownOutputPrefix(Some('R')) => Some("did you mean `<r`?".to_string()),
+      ParseErrorKind::UnknownOutputPrefix(Some('L')) => Some("did you mean `<l`?".to_string()),
+      ParseErrorKind::UnknownOutputPrefix(_) => {
+        Some("use `<l` or `<r` for literal or regex output, `<e` or `<E` for stderr".to_string())
+      }
+      ParseErrorKind::MissingInputSpace => Some(format!("did you mean `> {}`?", rest)),
+      ParseErrorKind::SingleDollar => Some(format!("did you mean `$${}`?", rest)),
+      ParseErrorKind::UnknownDirective(_) => Some(format!(
+        "known directives are {}, use `$${}` for a command argument",
+        DIRECTIVES
+          .iter()
+          .map(|d| format!("`${}`", d))
+          .collect::<Vec<_>>()
+          .join(", "),
+        rest
+      )),
+    }
+  }
+
+  /// Renders the error with a snippet of the offending line, similar to rustc
+  pub fn render(&self) -> String {
+    let mut str = format!("{}: {}\n", style_red("error"), style_bold(self.message()));
+
+    if self.line == 0 {
+      str += &format!(" {} {}\n", style_blue("-->"), self.file_path);
+      return str;
+    }
+
+    let line_number = self.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    str += &format!(
+      "{}{} {}:{}:{}\n",
+      gutter,
+      style_blue("-->"),
+      self.file_path,
+      self.line,
+      self.column
+    );
+    str += &format!("{} {}\n", gutter, style_blue("|"));
+    str += &format!("{} {} {}\n", style_blue(&line_number), style_blue("|"), self.source);
+    str += &format!(
+      "{} {} {}{}\n",
+      gutter,
+      style_blue("|"),
+      " ".repeat(self.column - 1),
+      style_red("^".repeat(self.length.max(1)))
+    );
+
+    if let Some(suggestion) = self.suggestion() {
+      str += &format!("{} {} {}: {}\n", gutter, style_blue("="), style_bold("help"), suggestion);
+    }
+
+    str
+  }
+}
+
+impl Display for InteractionParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.line == 0 {
+      write!(f, "{}: {}", self.file_path, self.message())
+    } else {
+      write!(f, "{}:{}:{}: {}", self.file_path, self.line, self.column, self.message())
+    }
+  }
+}
@@ -0,0 +1,378 @@
+mod error;
+
+use std::fs;
+
+use regex::Regex;
+
+pub use error::{InteractionParseError, ParseErrorKind};
+
+#[derive(Clone)]
+pub struct InteractionTest {
+  pub name: String,
+  pub file_path: String,
+  pub command_arguments: Vec<String>,
+  pub tags: Vec<String>,
+  pub lines: Vec<InteractionLine>,
+  /// The exit code set with `$exit`, not checked if `None`
+  pub exit_code: Option<ExpectedExitCode>,
+}
+
+/// The exit code set with `$exit`
+#[derive(Debug, Clone, Copy)]
+pub struct ExpectedExitCode {
+  /// The line of the `$exit` directive
+  pub line_idx: usize,
+  pub code: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct InteractionLine {
+  pub line_idx: usize,
+  pub content: String,
+  pub kind: InteractionLineKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum InteractionLineKind {
+  Input,
+  /// Closes stdin, set with `$eof` or after the last input with `$close_stdin`
+  CloseInput,
+  OutputLiteral,
+  /// The regex compiled from the content of the line
+  OutputRegex(Regex),
+  /// A line the program needs to print to stderr
+  ErrorLiteral,
+  /// A regex a line the program prints to stderr needs to match
+  ErrorRegex(Regex),
+}
+
+impl InteractionLineKind {
+  /// Whether the line is checked against stderr instead of stdout
+  pub fn is_stderr(&self) -> bool {
+    matches!(self, Self::ErrorLiteral | Self::ErrorRegex(_))
+  }
+}
+
+/// The names of all directives, for error messages
+pub const DIRECTIVES: &[&str] = &["tag", "exit", "eof", "close_stdin"];
+
+/// A `$<name> <arguments>` line
+enum Directive {
+  /// `$tag <tags...>`
+  Tag(Vec<String>),
+  /// `$exit <code>`
+  Exit(i32),
+  /// `$eof`
+  Eof,
+  /// `$close_stdin`
+  CloseStdin,
+}
+
+/// Parses the directive in `line`, which starts with a `$` followed by a letter.
+///
+/// Returns the column, the length and the kind of the error if the directive is invalid.
+fn parse_directive(line: &str) -> Result<Directive, (usize, usize, ParseErrorKind)> {
+  let (name, arguments) = line[1..].split_once(' ').unwrap_or((&line[1..], ""));
+
+  match name {
+    "tag" | "tags" => Ok(Directive::Tag(
+      arguments.split_whitespace().map(str::to_string).collect(),
+    )),
+    "exit" => arguments.trim().parse().map(Directive::Exit).map_err(|_| {
+      (
+        name.chars().count() + 3,
+        arguments.chars().count().max(1),
+        ParseErrorKind::InvalidExitCode(arguments.to_string()),
+      )
+    }),
+    "eof" => Ok(Directive::Eof),
+    "close_stdin" => Ok(Directive::CloseStdin),
+    _ => Err((
+      1,
+      name.chars().count() + 1,
+      ParseErrorKind::UnknownDirective(name.to_string()),
+    )),
+  }
+}
+
+pub fn parse(file_path: &String) -> Result<InteractionTest, InteractionParseError> {
+  let file_content =
+    fs::read_to_string(file_path).map_err(|e| InteractionParseError::io(file_path, e))?;
+
+  parse_content(file_path, &file_content)
+}
+
+/// Parses the content of the interaction file at `file_path`
+fn parse_content(file_path: &str, file_content: &str) -> Result<InteractionTest, InteractionParseError> {
+  let invalid_line = |line_idx: usize, line: &str, column: usize, length: usize, kind| {
+    InteractionParseError {
+      file_path: file_path.to_string(),
+      line: line_idx + 1,
+      column,
+      length,
+      source: line.to_string(),
+      kind,
+    }
+  };
+
+  let mut name = String::new();
+  let mut command_arguments: Vec<String> = Vec::new();
+  let mut tags: Vec<String> = Vec::new();
+  let mut exit_code = None;
+  // the line of the `$close_stdin` directive
+  let mut close_stdin = None;
+  // the line of the `$eof` directive
+  let mut eof = None;
+  let mut lines: Vec<InteractionLine> = Vec::new();
+
+  for (line_idx, line) in file_content.lines().enumerate() {
+    let char0 = line.chars().next().unwrap_or('\x0b');
+    let char1 = line.chars().nth(1).unwrap_or('\x0b');
+    let stripped_line = match line.char_indices().nth(2) {
+      Some((idx, _)) => &line[idx..],
+      None => "",
+    };
+    match char0 {
+      '#' => match char1 {
+        '#' => {
+          name = stripped_line.trim().to_string();
+        }
+        _ => {
+          // normal comment -> ignore
+        }
+      },
+      '$' => {
+        if char1 == '$' {
+          command_arguments.push(stripped_line.trim().to_string());
+        } else if char1.is_ascii_alphabetic() {
+          match parse_directive(line) {
+            Ok(Directive::Tag(new_tags)) => tags.extend(new_tags),
+            Ok(Directive::Exit(code)) => exit_code = Some(ExpectedExitCode { line_idx, code }),
+            Ok(Directive::Eof) => {
+              eof = Some(line_idx);
+              lines.push(InteractionLine {
+                line_idx,
+                content: String::new(),
+                kind: InteractionLineKind::CloseInput,
+              });
+            }
+            Ok(Directive::CloseStdin) => close_stdin = Some(line_idx),
+            Err((column, length, kind)) => {
+              return Err(invalid_line(line_idx, line, column, length, kind));
+            }
+          }
+        } else {
+          return Err(invalid_line(line_idx, line, 1, 1, ParseErrorKind::SingleDollar));
+        }
+      }
+      '>' => {
+        if let Some(eof_line) = eof {
+          return Err(invalid_line(
+            line_idx,
+            line,
+            1,
+            line.chars().count(),
+            ParseErrorKind::InputAfterEof(eof_line + 1),
+          ));
+        }
+        if char1 == ' ' {
+          lines.push(InteractionLine {
+            line_idx,
+            content: stripped_line.to_string(),
+            kind: InteractionLineKind::Input,
+          });
+        } else {
+          return Err(invalid_line(line_idx, line, 2, 1, ParseErrorKind::MissingInputSpace));
+        }
+      }
+      '<' => match char1 {
+        'r' | 'E' => {
+          let regex = Regex::new(stripped_line).map_err(|e| {
+            invalid_line(
+              line_idx,
+              line,
+              3,
+              stripped_line.chars().count(),
+              ParseErrorKind::InvalidRegex(e),
+            )
+          })?;
+
+          lines.push(InteractionLine {
+            line_idx,
+            content: stripped_line.to_string(),
+            kind: if char1 == 'r' {
+              InteractionLineKind::OutputRegex(regex)
+            } else {
+              InteractionLineKind::ErrorRegex(regex)
+            },
+          });
+        }
+        'l' | 'e' => {
+          lines.push(InteractionLine {
+            line_idx,
+            content: stripped_line.to_string(),
+            kind: if char1 == 'l' {
+              InteractionLineKind::OutputLiteral
+            } else {
+              InteractionLineKind::ErrorLiteral
+            },
+          });
+        }
+        '\x0b' => {
+          return Err(invalid_line(
+            line_idx,
+            line,
+            1,
+            1,
+            ParseErrorKind::UnknownOutputPrefix(None),
+          ));
+        }
+        c => {
+          return Err(invalid_line(
+            line_idx,
+            line,
+            1,
+            2,
+            ParseErrorKind::UnknownOutputPrefix(Some(c)),
+          ));
+        }
+      },
+      _ => {
+        lines.push(InteractionLine {
+          line_idx,
+          content: line.to_string(),
+          kind: InteractionLineKind::OutputLiteral,
+        });
+      }
+    }
+  }
+  if let (Some(line_idx), None) = (close_stdin, eof) {
+    let after_last_input = lines
+      .iter()
+      .rposition(|line| matches!(line.kind, InteractionLineKind::Input))
+      .map_or(0, |index| index + 1);
+    lines.insert(
+      after_last_input,
+      InteractionLine {
+        line_idx,
+        content: String::new(),
+        kind: InteractionLineKind::CloseInput,
+      },
+    );
+  }
+
+  Ok(InteractionTest {
+    name,
+    file_path: file_path.to_string(),
+    command_arguments,
+    tags,
+    lines,
+    exit_code,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse_lines(lines: &[&str]) -> Result<InteractionTest, InteractionParseError> {
+    parse_content("test.txt", &lines.join("\n"))
+  }
+
+  /// The line, column and length of the error in the only line
+  fn error_position(line: &str) -> (usize, usize, usize, ParseErrorKind) {
+    let error = parse_lines(&[line]).err().expect("line should not parse");
+    (error.line, error.column, error.length, error.kind)
+  }
+
+  fn kinds(interaction: &InteractionTest) -> Vec<String> {
+    interaction
+      .lines
+      .iter()
+      .map(|line| match line.kind {
+        InteractionLineKind::Input => format!("> {}", line.content),
+        InteractionLineKind::CloseInput => "$eof".to_string(),
+        _ => line.content.clone(),
+      })
+      .collect()
+  }
+
+  #[test]
+  fn error_positions() {
+    assert!(matches!(
+      error_position("<x foo"),
+      (1, 1, 2, ParseErrorKind::UnknownOutputPrefix(Some('x')))
+    ));
+    assert!(matches!(
+      error_position("<"),
+      (1, 1, 1, ParseErrorKind::UnknownOutputPrefix(None))
+    ));
+    assert!(matches!(error_position(">foo"), (1, 2, 1, ParseErrorKind::MissingInputSpace)));
+    assert!(matches!(error_position("$ foo"), (1, 1, 1, ParseErrorKind::SingleDollar)));
+    assert!(matches!(
+      error_position("$foo bar"),
+      (1, 1, 4, ParseErrorKind::UnknownDirective(name)) if name == "foo"
+    ));
+    assert!(matches!(
+      error_position("$exit abc"),
+      (1, 7, 3, ParseErrorKind::InvalidExitCode(code)) if code == "abc"
+    ));
+    assert!(matches!(
+      error_position("$exit"),
+      (1, 7, 1, ParseErrorKind::InvalidExitCode(code)) if code.is_empty()
+    ));
+    assert!(matches!(error_position("<r(a"), (1, 3, 2, ParseErrorKind::InvalidRegex(_))));
+    assert!(matches!(error_position("<E(a"), (1, 3, 2, ParseErrorKind::InvalidRegex(_))));
+  }
+
+  #[test]
+  fn io_error_has_no_position() {
+    let error = parse(&"does/not/exist.txt".to_string()).err().unwrap();
+
+    assert!(matches!(error.kind, ParseErrorKind::Io(_)));
+    assert_eq!((error.line, error.column), (0, 0));
+  }
+
+  #[test]
+  fn exit_code() {
+    let interaction = parse_lines(&["## exit", "> a", "$exit 3", "b"]).unwrap();
+    let exit_code = interaction.exit_code.unwrap();
+    assert_eq!((exit_code.line_idx, exit_code.code), (2, 3));
+
+    let interaction = parse_lines(&["$exit  -1 "]).unwrap();
+    assert_eq!(interaction.exit_code.unwrap().code, -1);
+
+    let interaction = parse_lines(&["> a", "b"]).unwrap();
+    assert!(interaction.exit_code.is_none());
+  }
+
+  #[test]
+  fn close_stdin_after_last_input() {
+    let interaction = parse_lines(&["$close_stdin", "> a", "b", "> c", "d", "e"]).unwrap();
+
+    assert_eq!(kinds(&interaction), ["> a", "b", "> c", "$eof", "d", "e"]);
+    assert_eq!(interaction.lines[3].line_idx, 0);
+  }
+
+  #[test]
+  fn close_stdin_without_input() {
+    let interaction = parse_lines(&["a", "$close_stdin"]).unwrap();
+
+    assert_eq!(kinds(&interaction), ["$eof", "a"]);
+  }
+
+  #[test]
+  fn close_stdin_with_eof() {
+    let interaction = parse_lines(&["> a", "$eof", "b", "$close_stdin"]).unwrap();
+
+    assert_eq!(kinds(&interaction), ["> a", "$eof", "b"]);
+  }
+
+  #[test]
+  fn input_after_eof() {
+    let error = parse_lines(&["> a", "$eof", "b", "> c"]).err().unwrap();
+
+    assert!(matches!(error.kind, ParseErrorKind::InputAfterEof(2)));
+    assert_eq!((error.line, error.column, error.length), (4, 1, 3));
+  }
+}
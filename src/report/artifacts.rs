@@ -0,0 +1,58 @@
+use std::{fs, path::PathBuf};
+
+use crate::runner::InteractionOutcome;
+
+use super::Reporter;
+
+/// Writes the whole conversation and stderr of every interaction that did not pass into a
+/// directory, one file per interaction
+pub struct ArtifactReporter {
+    dir: PathBuf,
+}
+
+impl ArtifactReporter {
+    pub fn new(dir: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("could not create '{}': {}", dir.display(), e))?;
+
+        Ok(Self { dir })
+    }
+
+    fn write(&self, outcome: &InteractionOutcome) -> Result<(), String> {
+        let interaction = &outcome.interaction;
+        let file_name = interaction
+            .file_path
+            .trim_start_matches("./")
+            .replace(std::path::is_separator, "_")
+            + ".log";
+        let path = self.dir.join(file_name);
+
+        let mut content = String::new();
+        content += &format!("name:     {}\n", interaction.name);
+        content += &format!("file:     {}\n", interaction.file_path);
+        content += &format!("status:   {}\n", outcome.status().name());
+        content += &format!("duration: {:.3}s\n", outcome.duration.as_secs_f64());
+        content += "\n--- transcript ---\n";
+        for line in &outcome.transcript {
+            content += line;
+            content += "\n";
+        }
+        content += "\n--- stderr ---\n";
+        content += &outcome.stderr;
+
+        fs::write(&path, content)
+            .map_err(|e| format!("could not write '{}': {}", path.display(), e))
+    }
+}
+
+impl Reporter for ArtifactReporter {
+    fn interaction_finished(&mut self, _index: usize, outcome: &InteractionOutcome) {
+        if outcome.result.is_ok() {
+            return;
+        }
+
+        if let Err(e) = self.write(outcome) {
+            eprintln!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::io::Write;
+
+use serde_json::{json, Value};
+
+use crate::{
+    parser::{InteractionParseError, InteractionTest},
+    runner::{InteractionOutcome, RunnerError, Status},
+};
+
+use super::{Reporter, Summary};
+
+/// Writes one JSON object per line for every event of the run
+pub struct JsonReporter {
+    writer: Box<dyn Write>,
+    parse_errors: usize,
+}
+
+impl JsonReporter {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            parse_errors: 0,
+        }
+    }
+
+    fn write(&mut self, event: Value) {
+        // a broken pipe should not abort the run
+        let _ = writeln!(self.writer, "{}", event);
+        let _ = self.writer.flush();
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn parse_failed(&mut self, error: &InteractionParseError) {
+        self.parse_errors += 1;
+        self.write(json!({
+            "event": "parse_error",
+            "file": error.file_path,
+            "line": error.line,
+            "column": error.column,
+            "message": error.message(),
+            "suggestion": error.suggestion(),
+        }));
+    }
+
+    fn run_started(&mut self, interactions: &[InteractionTest]) {
+        self.write(json!({
+            "event": "run_started",
+            "interactions": interactions.len(),
+        }));
+    }
+
+    fn interaction_started(&mut self, index: usize, interaction: &InteractionTest) {
+        self.write(json!({
+            "event": "interaction_started",
+            "index": index,
+            "name": interaction.name,
+            "file": interaction.file_path,
+            "tags": interaction.tags,
+        }));
+    }
+
+    fn interaction_finished(&mut self, index: usize, outcome: &InteractionOutcome) {
+        let mut event = json!({
+            "event": "interaction_finished",
+            "index": index,
+            "name": outcome.interaction.name,
+            "file": outcome.interaction.file_path,
+            "status": outcome.status().name(),
+            "duration_ms": outcome.duration.as_millis(),
+            "exit_code": outcome.exit_code,
+        });
+
+        if outcome.result.is_err() {
+            event["stderr"] = json!(outcome.stderr);
+        }
+
+        let details = match &outcome.result {
+            Ok(_) => json!({}),
+            Err(RunnerError::Fail {
+                line,
+                expected,
+                found,
+                prev_output,
+                mismatches,
+                ..
+            }) => json!({
+                "line": line + 1,
+                "expected": expected,
+                "found": found,
+                "prev_output": prev_output,
+                "mismatches": mismatches
+                    .iter()
+                    .map(|m| json!({
+                        "line": m.line + 1,
+                        "expected": m.expected,
+                        "found": m.found,
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+            Err(RunnerError::Error {
+                line,
+                prev_output,
+                ..
+            }) => json!({
+                "line": line + 1,
+                "prev_output": prev_output,
+            }),
+            Err(RunnerError::Start { error_message, .. }) => json!({
+                "message": error_message,
+            }),
+            Err(RunnerError::Crash {
+                line,
+                prev_output,
+                ..
+            }) => json!({
+                "line": line + 1,
+                "prev_output": prev_output,
+            }),
+            Err(
+                RunnerError::Timeout {
+                    elapsed,
+                    prev_output,
+                    ..
+                }
+                | RunnerError::Cancelled {
+                    elapsed,
+                    prev_output,
+                    ..
+                },
+            ) => json!({
+                "elapsed_ms": elapsed.as_millis(),
+                "prev_output": prev_output,
+            }),
+            Err(RunnerError::ExitCode {
+                line,
+                expected,
+                prev_output,
+                ..
+            }) => json!({
+                "line": line + 1,
+                "expected_exit_code": expected,
+                "prev_output": prev_output,
+            }),
+        };
+
+        if let (Value::Object(event), Value::Object(details)) = (&mut event, details) {
+            event.extend(details);
+        }
+
+        self.write(event);
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<(), String> {
+        self.write(json!({
+            "event": "run_finished",
+            "passed": summary.count(Status::Passed),
+            "failed": summary.count(Status::Failed),
+            "errored": summary.count(Status::Errored),
+            "timed_out": summary.count(Status::TimedOut),
+            "cancelled": summary.count(Status::Cancelled),
+            "parse_errors": self.parse_errors,
+            "duration_ms": summary.duration.as_millis(),
+            "slowest": summary
+                .slowest
+                .iter()
+                .map(|outcome| json!({
+                    "name": outcome.interaction.name,
+                    "file": outcome.interaction.file_path,
+                    "duration_ms": outcome.duration.as_millis(),
+                }))
+                .collect::<Vec<_>>(),
+        }));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use crate::{
+    parser::InteractionParseError,
+    runner::{InteractionOutcome, RunnerError, Status},
+};
+
+use super::Summary;
+
+pub fn render(summary: &Summary, parse_errors: &[InteractionParseError]) -> String {
+    let outcomes = summary.outcomes;
+    let tests = outcomes.len() + parse_errors.len();
+    let failures = summary.count(Status::Failed);
+    let errors =
+        summary.count(Status::Errored) + summary.count(Status::TimedOut) + parse_errors.len();
+    let skipped = summary.count(Status::Cancelled);
+    let time = seconds(summary.duration);
+
+    let mut xml = String::new();
+    xml += "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+    xml += &format!(
+        "<testsuites name=\"ocd\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">\n",
+        tests, failures, errors, time
+    );
+    xml += &format!(
+        "  <testsuite name=\"ocd\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">\n",
+        tests, failures, errors, skipped, time
+    );
+
+    xml += &render_properties(summary);
+
+    for error in parse_errors {
+        xml += &render_parse_error(error);
+    }
+
+    for outcome in outcomes {
+        xml += &render_testcase(outcome);
+    }
+
+    xml += "  </testsuite>\n";
+    xml += "</testsuites>\n";
+
+    xml
+}
+
+fn render_testcase(outcome: &InteractionOutcome) -> String {
+    let interaction = &outcome.interaction;
+    let name = if interaction.name.is_empty() {
+        &interaction.file_path
+    } else {
+        &interaction.name
+    };
+
+    let mut xml = format!(
+        "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" time=\"{}\"",
+        escape(name),
+        escape(&interaction.file_path),
+        escape(&interaction.file_path),
+        seconds(outcome.duration)
+    );
+
+    let (tag, message, details, prev_output) = match &outcome.result {
+        Ok(_) => {
+            xml += " />\n";
+            return xml;
+        }
+        Err(RunnerError::Fail {
+            line,
+            expected,
+            found,
+            prev_output,
+            mismatches,
+            ..
+        }) => (
+            "failure",
+            format!("line {}: expected '{}' but found '{}'", line + 1, expected, found),
+            mismatches
+                .iter()
+                .map(|m| {
+                    format!(
+                        "line {}:\nexpected: '{}'\nfound:    '{}'",
+                        m.line + 1,
+                        m.expected,
+                        m.found
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            prev_output,
+        ),
+        Err(RunnerError::Error {
+            line,
+            error_message,
+            error_code,
+            prev_output,
+            ..
+        }) => (
+            "error",
+            match error_code {
+                Some(code) => format!("line {}: program exited with error code {}", line + 1, code),
+                None => format!("line {}: program was terminated by a signal", line + 1),
+            },
+            error_message.to_string(),
+            prev_output,
+        ),
+        Err(RunnerError::Start { error_message, .. }) => {
+            ("error", error_message.to_string(), String::new(), &Vec::new())
+        }
+        Err(RunnerError::Crash {
+            line,
+            error_message,
+            error_code,
+            prev_output,
+            ..
+        }) => (
+            "error",
+            match error_code {
+                Some(code) => format!(
+                    "line {}: program crashed after all expected output with error code {}",
+                    line + 1,
+                    code
+                ),
+                None => format!(
+                    "line {}: program was terminated by a signal after all expected output",
+                    line + 1
+                ),
+            },
+            error_message.to_string(),
+            prev_output,
+        ),
+        Err(RunnerError::Timeout {
+            elapsed,
+            prev_output,
+            ..
+        }) => (
+            "error",
+            format!("program was killed after {:.1}s", elapsed.as_secs_f64()),
+            String::new(),
+            prev_output,
+        ),
+        Err(RunnerError::Cancelled {
+            elapsed,
+            prev_output,
+            ..
+        }) => (
+            "skipped",
+            format!("cancelled after {:.1}s", elapsed.as_secs_f64()),
+            String::new(),
+            prev_output,
+        ),
+        Err(RunnerError::ExitCode {
+            line,
+            expected,
+            found,
+            error_message,
+            prev_output,
+            ..
+        }) => (
+            "failure",
+            match found {
+                Some(code) => format!(
+                    "line {}: expected exit code {} but found {}",
+                    line + 1,
+                    expected,
+                    code
+                ),
+                None => format!(
+                    "line {}: expected exit code {} but the program was terminated by a signal",
+                    line + 1,
+                    expected
+                ),
+            },
+            error_message.to_string(),
+            prev_output,
+        ),
+    };
+
+    xml += ">\n";
+    xml += &format!(
+        "      <{} message=\"{}\">{}\n\nPrevious output:\n{}</{}>\n",
+        tag,
+        escape(&message),
+        escape(details.trim_end()),
+        escape(&prev_output.join("\n")),
+        tag
+    );
+    xml += "    </testcase>\n";
+
+    xml
+}
+
+/// The slowest interactions as `slowest.<rank>` properties
+fn render_properties(summary: &Summary) -> String {
+    if summary.slowest.is_empty() {
+        return String::new();
+    }
+
+    let mut xml = "    <properties>\n".to_string();
+    for (rank, outcome) in summary.slowest.iter().enumerate() {
+        xml += &format!(
+            "      <property name=\"slowest.{}\" value=\"{}s {}\" />\n",
+            rank + 1,
+            seconds(outcome.duration),
+            escape(&outcome.interaction.file_path)
+        );
+    }
+    xml += "    </properties>\n";
+
+    xml
+}
+
+fn render_parse_error(error: &InteractionParseError) -> String {
+    let mut xml = format!(
+        "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" time=\"0.000\">\n",
+        escape(&error.file_path),
+        escape(&error.file_path),
+        escape(&error.file_path),
+    );
+    xml += &format!(
+        "      <error message=\"{}\" type=\"parse\">{}</error>\n",
+        escape(&error.message()),
+        escape(&error.to_string())
+    );
+    xml += "    </testcase>\n";
+
+    xml
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&apos;",
+            // characters not allowed in xml 1.0
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
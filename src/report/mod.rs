@@ -0,0 +1,137 @@
+mod artifacts;
+mod json;
+mod junit;
+mod progress;
+mod summary;
+
+use std::{fs, path::PathBuf, str::FromStr};
+
+use crate::{
+    parser::{InteractionParseError, InteractionTest},
+    runner::InteractionOutcome,
+};
+
+pub use artifacts::ArtifactReporter;
+pub use json::JsonReporter;
+pub use progress::ProgressReporter;
+pub use summary::Summary;
+
+/// Receives the events of a run.
+///
+/// `parse_failed` is sent for every interaction file that could not be parsed before the run
+/// starts, the `run_started` and `interaction_*` events are sent by the runner and `finish` is
+/// called once all outcomes are known.
+pub trait Reporter {
+    fn parse_failed(&mut self, _error: &InteractionParseError) {}
+
+    fn run_started(&mut self, _interactions: &[InteractionTest]) {}
+
+    fn interaction_started(&mut self, _index: usize, _interaction: &InteractionTest) {}
+
+    fn interaction_finished(&mut self, _index: usize, _outcome: &InteractionOutcome) {}
+
+    fn finish(&mut self, _summary: &Summary) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl Reporter for Vec<Box<dyn Reporter>> {
+    fn parse_failed(&mut self, error: &InteractionParseError) {
+        for reporter in self {
+            reporter.parse_failed(error);
+        }
+    }
+
+    fn run_started(&mut self, interactions: &[InteractionTest]) {
+        for reporter in self {
+            reporter.run_started(interactions);
+        }
+    }
+
+    fn interaction_started(&mut self, index: usize, interaction: &InteractionTest) {
+        for reporter in self {
+            reporter.interaction_started(index, interaction);
+        }
+    }
+
+    fn interaction_finished(&mut self, index: usize, outcome: &InteractionOutcome) {
+        for reporter in self {
+            reporter.interaction_finished(index, outcome);
+        }
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<(), String> {
+        for reporter in self {
+            reporter.finish(summary)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A report file requested with `--report <format>=<file>`
+#[derive(Clone)]
+pub struct ReportTarget {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl FromStr for ReportTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected '<format>=<file>', found '{}'", s))?;
+
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            _ => return Err(format!("unknown report format '{}'", format)),
+        };
+
+        Ok(ReportTarget {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+impl ReportTarget {
+    pub fn reporter(&self) -> Result<Box<dyn Reporter>, String> {
+        match self.format {
+            ReportFormat::Junit => Ok(Box::new(JunitReporter {
+                path: self.path.clone(),
+                parse_errors: Vec::new(),
+            })),
+            ReportFormat::Json => {
+                let file = fs::File::create(&self.path).map_err(|e| {
+                    format!("could not create report '{}': {}", self.path.display(), e)
+                })?;
+                Ok(Box::new(JsonReporter::new(Box::new(file))))
+            }
+        }
+    }
+}
+
+struct JunitReporter {
+    path: PathBuf,
+    parse_errors: Vec<InteractionParseError>,
+}
+
+impl Reporter for JunitReporter {
+    fn parse_failed(&mut self, error: &InteractionParseError) {
+        self.parse_errors.push(error.clone());
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<(), String> {
+        fs::write(&self.path, junit::render(summary, &self.parse_errors))
+            .map_err(|e| format!("could not write report '{}': {}", self.path.display(), e))
+    }
+}
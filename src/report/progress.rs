@@ -0,0 +1,216 @@
+use std::io::{stdout, Write};
+
+use crate::{
+    parser::{InteractionParseError, InteractionTest},
+    runner::{InteractionOutcome, RunnerError, Status},
+    style::style_bold,
+};
+
+use super::{Reporter, Summary};
+
+struct Icons {
+    none: &'static str,
+    ok: &'static str,
+    fail: &'static str,
+    error: &'static str,
+    timeout: &'static str,
+    cancelled: &'static str,
+}
+
+const EMOJI_ICONS: Icons = Icons {
+    none: "⬜",
+    ok: "✅",
+    fail: "🟥",
+    error: "💀",
+    timeout: "⏰",
+    cancelled: "🚫",
+};
+
+const ASCII_ICONS: Icons = Icons {
+    none: "-",
+    ok: ".",
+    fail: "F",
+    error: "E",
+    timeout: "T",
+    cancelled: "C",
+};
+
+trait ToIcon {
+    fn to_icon(&self, icons: &Icons) -> &'static str;
+}
+
+impl ToIcon for Result<(), RunnerError> {
+    fn to_icon(&self, icons: &Icons) -> &'static str {
+        match self {
+            Ok(_) => icons.ok,
+            Err(RunnerError::Fail { .. } | RunnerError::ExitCode { .. }) => icons.fail,
+            Err(RunnerError::Error { .. } | RunnerError::Start { .. } | RunnerError::Crash { .. }) => {
+                icons.error
+            }
+            Err(RunnerError::Timeout { .. }) => icons.timeout,
+            Err(RunnerError::Cancelled { .. }) => icons.cancelled,
+        }
+    }
+}
+
+impl ToIcon for InteractionOutcome {
+    fn to_icon(&self, icons: &Icons) -> &'static str {
+        self.result.to_icon(icons)
+    }
+}
+
+fn print_flush(message: String) {
+    print!("{}", message);
+    stdout().flush().unwrap();
+}
+
+fn print_over(message: String) {
+    print!("\r{}", message);
+    stdout().flush().unwrap();
+}
+
+/// The human readable output: a progress bar while running and all failures at the end
+pub struct ProgressReporter {
+    /// Print one line per interaction instead of a single progress bar
+    sequential: bool,
+    /// How many lines of output before a failure are shown
+    context_lines: usize,
+    /// Use plain characters instead of emoji
+    ascii: bool,
+    /// Redraw the progress with `\r`. Without it only finished interactions are printed, which
+    /// keeps logs of non-interactive terminals readable
+    redraw: bool,
+    icons: Vec<&'static str>,
+    /// The amount of interactions the run was started with
+    interactions: usize,
+    parse_errors: usize,
+}
+
+impl ProgressReporter {
+    pub fn new(sequential: bool, context_lines: usize, ascii: bool, redraw: bool) -> Self {
+        Self {
+            sequential,
+            context_lines,
+            ascii,
+            redraw,
+            icons: Vec::new(),
+            interactions: 0,
+            parse_errors: 0,
+        }
+    }
+
+    fn icon_set(&self) -> &'static Icons {
+        if self.ascii {
+            &ASCII_ICONS
+        } else {
+            &EMOJI_ICONS
+        }
+    }
+
+    fn progress_bar(&self) -> String {
+        self.icons.join("")
+    }
+}
+
+impl Reporter for ProgressReporter {
+    fn parse_failed(&mut self, error: &InteractionParseError) {
+        self.parse_errors += 1;
+        println!("{}", error.render());
+    }
+
+    fn run_started(&mut self, interactions: &[InteractionTest]) {
+        self.interactions = interactions.len();
+        if !self.sequential && self.redraw {
+            self.icons = vec![self.icon_set().none; interactions.len()];
+            print_flush(self.progress_bar());
+        }
+    }
+
+    fn interaction_started(&mut self, _index: usize, interaction: &InteractionTest) {
+        if self.sequential && self.redraw {
+            print_flush(format!("{} {}", self.icon_set().none, interaction.name));
+        }
+    }
+
+    fn interaction_finished(&mut self, index: usize, outcome: &InteractionOutcome) {
+        let icon = outcome.to_icon(self.icon_set());
+
+        match (self.sequential, self.redraw) {
+            (true, true) => {
+                print_over(icon.to_string());
+                println!();
+            }
+            (true, false) => println!("{} {}", icon, outcome.interaction.name),
+            (false, true) => {
+                self.icons[index] = icon;
+                print_over(self.progress_bar());
+            }
+            // the icons are printed in the order the interactions finish
+            (false, false) => print_flush(icon.to_string()),
+        }
+    }
+
+    fn finish(&mut self, summary: &Summary) -> Result<(), String> {
+        if !self.sequential {
+            println!();
+        }
+
+        let outcomes = summary.outcomes;
+        let fails: Vec<&RunnerError> = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.result.as_ref().err())
+            .collect();
+
+        // a cancelled or failed fast run stops before all interactions ran
+        let not_run = self.interactions - outcomes.len();
+
+        if fails.is_empty() && not_run == 0 && self.parse_errors == 0 {
+            if self.ascii {
+                println!("All interactions passed");
+            } else {
+                println!("All interactions passed 🎉");
+            }
+        }
+
+        for fail in fails {
+            println!("{}", fail.render(self.context_lines));
+        }
+
+        let mut counts: Vec<String> = Status::ALL
+            .iter()
+            .map(|status| (summary.count(*status), status.name().replace('_', " ")))
+            .chain([(not_run, "not run".to_string())])
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, status)| format!("{} {}", count, status))
+            .collect();
+        if counts.is_empty() {
+            counts.push("0 interactions".to_string());
+        }
+        println!(
+            "{} {} in {:.2}s",
+            style_bold("Summary:"),
+            counts.join(", "),
+            summary.duration.as_secs_f64()
+        );
+
+        if !summary.slowest.is_empty() {
+            println!("{}", style_bold("Slowest:"));
+            for outcome in &summary.slowest {
+                println!(
+                    "{:>8.2}s  {} ({})",
+                    outcome.duration.as_secs_f64(),
+                    outcome.interaction.name,
+                    outcome.interaction.file_path
+                );
+            }
+        }
+
+        match self.parse_errors {
+            0 => {}
+            1 => println!("1 interaction file could not be parsed"),
+            n => println!("{} interaction files could not be parsed", n),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use crate::runner::{InteractionOutcome, Status};
+
+/// The statistics of a finished run
+pub struct Summary<'a> {
+    pub outcomes: &'a [InteractionOutcome],
+    /// The wall time of the whole run
+    pub duration: Duration,
+    /// The interactions that took the longest, the slowest first
+    pub slowest: Vec<&'a InteractionOutcome>,
+}
+
+impl<'a> Summary<'a> {
+    pub fn new(outcomes: &'a [InteractionOutcome], duration: Duration, slowest: usize) -> Self {
+        let mut by_duration: Vec<&InteractionOutcome> = outcomes.iter().collect();
+        by_duration.sort_by_key(|outcome| std::cmp::Reverse(outcome.duration));
+        by_duration.truncate(slowest);
+
+        Self {
+            outcomes,
+            duration,
+            slowest: by_duration,
+        }
+    }
+
+    /// The amount of outcomes with the given status
+    pub fn count(&self, status: Status) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.status() == status)
+            .count()
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether interactions are running right now
+static RUNNING: AtomicBool = AtomicBool::new(false);
+/// Set once the current run was cancelled with Ctrl+C
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+/// Set once the current run reached the maximum amount of failures
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Cancels the current run.
+///
+/// Returns `false` if no interactions are running.
+pub fn cancel() -> bool {
+    CANCELLED.store(true, Ordering::SeqCst);
+    RUNNING.load(Ordering::SeqCst)
+}
+
+/// Whether the last run was cancelled
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Stops the current run because too many interactions failed
+pub(super) fn fail() {
+    FAILED.store(true, Ordering::SeqCst);
+}
+
+/// Whether no new interactions should be started and the running ones should be killed
+pub(super) fn should_stop() -> bool {
+    CANCELLED.load(Ordering::SeqCst) || FAILED.load(Ordering::SeqCst)
+}
+
+pub(super) fn run_started() {
+    CANCELLED.store(false, Ordering::SeqCst);
+    FAILED.store(false, Ordering::SeqCst);
+    RUNNING.store(true, Ordering::SeqCst);
+}
+
+pub(super) fn run_finished() {
+    RUNNING.store(false, Ordering::SeqCst);
+}
@@ -1,8 +1,24 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
+
+use regex::Regex;
 
 #[derive(Clone)]
 pub struct RunnerConfig {
     pub command: String,
     pub arguments: Vec<String>,
+    pub env: Vec<(String, String)>,
+    /// The directory the program is started in, defaults to the directory of the interaction file
+    pub working_dir: Option<PathBuf>,
     pub timeout: Duration,
+    /// How long the program may run in total before it is killed
+    pub total_timeout: Duration,
+    /// Keep running after the first mismatch to record the whole conversation for a diff
+    pub diff: bool,
+    /// Keep running after the first mismatch to find all mismatching lines
+    pub collect_all: bool,
+    /// A program that exits with an error and whose stderr matches one of these is treated as if
+    /// it exited normally
+    pub ignored_crashes: Vec<Regex>,
+    /// Stop the run once this many interactions did not pass
+    pub max_failures: Option<usize>,
 }
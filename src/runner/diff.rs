@@ -0,0 +1,70 @@
+use similar::{ChangeTag, DiffOp, TextDiff};
+
+use crate::style::{style_blue, style_green, style_green_emphasized, style_red, style_red_emphasized};
+
+/// How many unchanged lines are shown around every change
+const CONTEXT_LINES: usize = 3;
+
+/// Renders a unified diff of the expected and the actual conversation, highlighting the changed
+/// characters of lines that only differ slightly
+pub fn render_diff(expected: &[String], actual: &[String]) -> String {
+    let expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+    let actual: Vec<&str> = actual.iter().map(String::as_str).collect();
+
+    let diff = TextDiff::from_slices(&expected, &actual);
+
+    let mut str = String::new();
+    str += &style_red("--- expected");
+    str += "\n";
+    str += &style_green("+++ actual");
+    str += "\n";
+
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        str += &style_blue(hunk_header(&group));
+        str += "\n";
+
+        for op in &group {
+            for change in diff.iter_inline_changes(op) {
+                let sign = match change.tag() {
+                    ChangeTag::Delete => "-",
+                    ChangeTag::Insert => "+",
+                    ChangeTag::Equal => " ",
+                };
+
+                str += &style_change(change.tag(), false, sign);
+                for (emphasized, value) in change.iter_strings_lossy() {
+                    str += &style_change(change.tag(), emphasized, &value);
+                }
+                str += "\n";
+            }
+        }
+    }
+
+    str
+}
+
+fn style_change(tag: ChangeTag, emphasized: bool, message: &str) -> String {
+    match (tag, emphasized) {
+        (ChangeTag::Delete, false) => style_red(message),
+        (ChangeTag::Delete, true) => style_red_emphasized(message),
+        (ChangeTag::Insert, false) => style_green(message),
+        (ChangeTag::Insert, true) => style_green_emphasized(message),
+        (ChangeTag::Equal, _) => message.to_string(),
+    }
+}
+
+fn hunk_header(group: &[DiffOp]) -> String {
+    let (first, last) = (&group[0], &group[group.len() - 1]);
+    let old_start = first.old_range().start;
+    let new_start = first.new_range().start;
+    let old_len = last.old_range().end - old_start;
+    let new_len = last.new_range().end - new_start;
+
+    format!(
+        "@@ -{},{} +{},{} @@",
+        old_start + 1,
+        old_len,
+        new_start + 1,
+        new_len
+    )
+}
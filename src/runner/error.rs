@@ -1,6 +1,28 @@
-use std::fmt::Display;
+use std::time::Duration;
 
-use crate::parser::InteractionTest;
+use crate::{
+    parser::InteractionTest,
+    style::{style_bold, style_error, style_fail, style_green, style_red},
+};
+
+use super::diff::render_diff;
+
+/// The whole conversation with the program, recorded when continuing after a mismatch
+#[derive(Default)]
+pub struct Transcript {
+    /// The inputs and expected outputs, matched regex lines are replaced by the line they matched
+    pub expected: Vec<String>,
+    /// The inputs and the outputs the program actually printed
+    pub actual: Vec<String>,
+}
+
+/// An expected output line the program did not print
+#[derive(Clone)]
+pub struct Mismatch {
+    pub line: usize,
+    pub expected: String,
+    pub found: String,
+}
 
 pub enum RunnerError {
     Fail {
@@ -9,42 +31,61 @@ pub enum RunnerError {
         expected: String,
         found: String,
         prev_output: Vec<String>,
+        /// All mismatches when collecting all of them, otherwise only the first one
+        mismatches: Vec<Mismatch>,
+        transcript: Option<Transcript>,
     },
     Error {
         interaction: InteractionTest,
         line: usize,
         error_message: String,
-        error_code: i32,
+        /// `None` if the program was terminated by a signal
+        error_code: Option<i32>,
+        prev_output: Vec<String>,
+    },
+    /// The program could not be started
+    Start {
+        interaction: InteractionTest,
+        error_message: String,
+    },
+    /// The program printed all expected output and exited with an error afterwards
+    Crash {
+        interaction: InteractionTest,
+        line: usize,
+        error_message: String,
+        /// `None` if the program was terminated by a signal
+        error_code: Option<i32>,
+        prev_output: Vec<String>,
+    },
+    /// The program did not exit in time and was killed
+    Timeout {
+        interaction: InteractionTest,
+        elapsed: Duration,
+        prev_output: Vec<String>,
+    },
+    /// The run was cancelled while the interaction was running
+    Cancelled {
+        interaction: InteractionTest,
+        elapsed: Duration,
+        prev_output: Vec<String>,
+    },
+    /// The output was correct, but the program exited with another code than set with `$exit`
+    ExitCode {
+        interaction: InteractionTest,
+        /// The line of the `$exit` directive
+        line: usize,
+        expected: i32,
+        /// `None` if the program was terminated by a signal
+        found: Option<i32>,
+        error_message: String,
         prev_output: Vec<String>,
     },
 }
 
-fn style<D: Display>(message: D, code: &str) -> String {
-    format!("\x1b[{}m{}\x1b[0m", code, message)
-}
-
-fn style_fail(message: &str) -> String {
-    style(message, "1;91")
-}
-
-fn style_error(message: &str) -> String {
-    style(message, "1;30;41")
-}
-
-fn style_bold<D: Display>(message: D) -> String {
-    style(message, "1")
-}
-
-fn style_green<D: Display>(message: D) -> String {
-    style(message, "32")
-}
-
-fn style_red<D: Display>(message: D) -> String {
-    style(message, "91")
-}
-
-impl ToString for RunnerError {
-    fn to_string(&self) -> String {
+impl RunnerError {
+    /// Renders the error for the terminal, showing up to `context_lines` lines of the output
+    /// before the error
+    pub fn render(&self, context_lines: usize) -> String {
         let mut str = String::new();
 
         let (mut prev_output, error) = match self {
@@ -54,24 +95,47 @@ impl ToString for RunnerError {
                 expected,
                 found,
                 prev_output,
+                mismatches,
+                transcript,
             } => {
                 str += format!(
                     "{} {}:{}\n",
                     style_fail("Failed"),
                     interaction.file_path,
-                    line
+                    line + 1
                 )
                 .as_str();
                 str += format!(
                     "{} in line {}\n",
                     style_bold(&interaction.name),
-                    style_bold(line)
+                    style_bold(line + 1)
                 )
                 .as_str();
                 str += format!("expected: '{}'\n", expected).as_str();
                 str += format!("found:    '{}'\n", found).as_str();
 
-                (prev_output.to_owned(), found)
+                if mismatches.len() > 1 {
+                    str += style_bold(format!("{} mismatching lines:\n", mismatches.len()))
+                        .as_str();
+                    for mismatch in mismatches {
+                        str += format!(
+                            "line {}: expected '{}', found '{}'\n",
+                            mismatch.line + 1,
+                            mismatch.expected,
+                            mismatch.found
+                        )
+                        .as_str();
+                    }
+                }
+
+                if let Some(transcript) = transcript {
+                    str += style_bold("Diff:\n").as_str();
+                    str += render_diff(&transcript.expected, &transcript.actual).as_str();
+
+                    return str;
+                }
+
+                (prev_output.to_owned(), found.as_str())
             }
             RunnerError::Error {
                 interaction,
@@ -84,34 +148,155 @@ impl ToString for RunnerError {
                     "{} in {}:{}\n",
                     style_error("Error"),
                     interaction.file_path,
-                    line
+                    line + 1
+                )
+                .as_str();
+                match error_code {
+                    Some(code) => {
+                        str += format!(
+                            "{} in line {} with error code {}\n",
+                            style_bold(&interaction.name),
+                            style_bold(line + 1),
+                            style_bold(code)
+                        )
+                        .as_str()
+                    }
+                    None => {
+                        str += format!(
+                            "{} in line {}, terminated by a signal\n",
+                            style_bold(&interaction.name),
+                            style_bold(line + 1)
+                        )
+                        .as_str()
+                    }
+                }
+                str += format!("{}\n", error_message).as_str();
+
+                (prev_output.to_owned(), error_message.as_str())
+            }
+            RunnerError::Start {
+                interaction,
+                error_message,
+            } => {
+                str += format!("{} in {}\n", style_error("Error"), interaction.file_path).as_str();
+                str += format!("{}\n", error_message).as_str();
+
+                return str;
+            }
+            RunnerError::Crash {
+                interaction,
+                line,
+                error_message,
+                error_code,
+                prev_output,
+            } => {
+                str += format!(
+                    "{} in {}:{}\n",
+                    style_error("Error"),
+                    interaction.file_path,
+                    line + 1
                 )
                 .as_str();
+                match error_code {
+                    Some(code) => {
+                        str += format!(
+                            "{} crashed after all expected output with error code {}\n",
+                            style_bold(&interaction.name),
+                            style_bold(code)
+                        )
+                        .as_str()
+                    }
+                    None => {
+                        str += format!(
+                            "{} was terminated by a signal after all expected output\n",
+                            style_bold(&interaction.name)
+                        )
+                        .as_str()
+                    }
+                }
+                str += format!("{}\n", error_message).as_str();
+
+                (prev_output.to_owned(), error_message.as_str())
+            }
+            RunnerError::Timeout {
+                interaction,
+                elapsed,
+                prev_output,
+            } => {
+                str += format!("{} in {}\n", style_error("Timeout"), interaction.file_path).as_str();
                 str += format!(
-                    "{} in line {} with error code {}\n",
+                    "{} was killed after {:.1}s\n",
                     style_bold(&interaction.name),
-                    style_bold(line),
-                    style_bold(error_code)
+                    elapsed.as_secs_f64()
                 )
                 .as_str();
-                str += format!("{}\n", error_message).as_str();
 
-                (prev_output.to_owned(), error_message)
+                (prev_output.to_owned(), "")
+            }
+            RunnerError::Cancelled {
+                interaction,
+                elapsed,
+                prev_output,
+            } => {
+                str += format!("{} {}\n", style_bold("Cancelled"), interaction.file_path).as_str();
+                str += format!(
+                    "{} was killed after {:.1}s\n",
+                    style_bold(&interaction.name),
+                    elapsed.as_secs_f64()
+                )
+                .as_str();
+
+                (prev_output.to_owned(), "")
+            }
+            RunnerError::ExitCode {
+                interaction,
+                line,
+                expected,
+                found,
+                error_message,
+                prev_output,
+            } => {
+                str += format!(
+                    "{} {}:{}\n",
+                    style_fail("Failed"),
+                    interaction.file_path,
+                    line + 1
+                )
+                .as_str();
+                str += format!(
+                    "{} exited with the wrong exit code\n",
+                    style_bold(&interaction.name)
+                )
+                .as_str();
+                str += format!("expected: {}\n", expected).as_str();
+                match found {
+                    Some(code) => str += format!("found:    {}\n", code).as_str(),
+                    None => str += "found:    <terminated by a signal>\n",
+                }
+                if !error_message.is_empty() {
+                    str += format!("{}\n", error_message).as_str();
+                }
+
+                (prev_output.to_owned(), "")
             }
         };
 
+        if context_lines == 0 {
+            return str;
+        }
+
         str += style_bold("Previous output:\n").as_str();
 
         let mut prev_output_str = String::new();
 
-        if prev_output.len() > 10 {
+        if prev_output.len() > context_lines {
             prev_output_str += "[...]\n"
         } else {
             prev_output_str += "<start>\n"
         }
 
         prev_output.reverse();
-        prev_output.truncate(10);
+        prev_output.truncate(context_lines);
         prev_output.reverse();
 
         prev_output_str += prev_output.join("\n").as_str();
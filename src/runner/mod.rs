@@ -1,8 +1,13 @@
+mod cancel;
 mod config;
+mod diff;
 mod error;
 mod multi_runner;
+mod outcome;
 mod single_runner;
 
+pub use cancel::{cancel, is_cancelled};
 pub use config::RunnerConfig;
 pub use error::RunnerError;
 pub use multi_runner::run_interactions as run;
+pub use outcome::{InteractionOutcome, Status};
@@ -1,178 +1,163 @@
 use std::{
-    io::{stdout, Write},
-    sync::mpsc,
-    thread::{self},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
 };
 
-use crate::{parser::InteractionTest, runner::single_runner};
+use crate::{parser::InteractionTest, report::Reporter, runner::single_runner};
 
-use super::{RunnerConfig, RunnerError};
+use super::{cancel, InteractionOutcome, RunnerConfig, Status};
 
-mod result_char {
-    pub const NONE: &str = "⬜";
-    pub const OK: &str = "✅";
-    pub const FAIL: &str = "🟥";
-    pub const ERROR: &str = "💀";
-}
+pub fn run_interactions(
+    interactions: Vec<InteractionTest>,
+    config: RunnerConfig,
+    thread_count: u64,
+    reporter: &mut dyn Reporter,
+) -> Vec<InteractionOutcome> {
+    reporter.run_started(&interactions);
+    cancel::run_started();
 
-type InteractionResult = Result<(), RunnerError>;
-type InteractionResultVec = Vec<Option<InteractionResult>>;
+    let outcomes = if thread_count == 0 {
+        run_sync(interactions, config, reporter)
+    } else {
+        run_parallel(interactions, config, thread_count, reporter)
+    };
 
-trait ToIcon {
-    fn to_icon(&self) -> &'static str;
+    cancel::run_finished();
+    outcomes
 }
 
-impl ToIcon for InteractionResult {
-    fn to_icon(&self) -> &'static str {
-        match self {
-            Ok(_) => result_char::OK,
-            Err(RunnerError::Fail { .. }) => result_char::FAIL,
-            Err(RunnerError::Error { .. }) => result_char::ERROR,
-        }
-    }
+/// Stops the run once `max` interactions did not pass
+struct FailureLimit {
+    max: Option<usize>,
+    failures: usize,
 }
 
-impl ToIcon for Option<InteractionResult> {
-    fn to_icon(&self) -> &'static str {
-        match self {
-            None => result_char::NONE,
-            Some(result) => result.to_icon(),
-        }
+impl FailureLimit {
+    fn new(max: Option<usize>) -> Self {
+        Self { max, failures: 0 }
     }
-}
 
-fn create_progress_bar(results: &InteractionResultVec) -> String {
-    results
-        .iter()
-        .map(|r| r.to_icon())
-        .collect::<Vec<&str>>()
-        .join("")
-}
+    fn record(&mut self, outcome: &InteractionOutcome) {
+        // interactions killed after the limit was reached do not count
+        if matches!(outcome.status(), Status::Passed | Status::Cancelled) {
+            return;
+        }
 
-fn update_is_done(results: &InteractionResultVec) -> bool {
-    for result in results {
-        if let None = result {
-            return false;
+        self.failures += 1;
+        if self.max.is_some_and(|max| self.failures >= max) {
+            cancel::fail();
         }
     }
-
-    return true;
-}
-
-fn print_flush(message: String) {
-    print!("{}", message);
-    stdout().flush().unwrap();
 }
 
-fn print_over(message: String) {
-    print!("\r{}", message);
-    stdout().flush().unwrap();
-}
-
-pub fn run_interactions(
+fn run_sync(
     interactions: Vec<InteractionTest>,
     config: RunnerConfig,
-    thread_count: u64,
-) -> Vec<RunnerError> {
-    if thread_count == 0 {
-        run_sync(interactions, config)
-    } else {
-        run_parallel(interactions, config, thread_count)
-    }
-}
+    reporter: &mut dyn Reporter,
+) -> Vec<InteractionOutcome> {
+    let mut outcomes = Vec::new();
+    let mut limit = FailureLimit::new(config.max_failures);
+
+    for (index, interaction) in interactions.into_iter().enumerate() {
+        if cancel::should_stop() {
+            break;
+        }
 
-fn run_sync(interactions: Vec<InteractionTest>, config: RunnerConfig) -> Vec<RunnerError> {
-    let mut fails = Vec::new();
+        reporter.interaction_started(index, &interaction);
 
-    for interaction in interactions {
-        print_flush(format!("{} {}", None.to_icon(), interaction.name));
+        let outcome = single_runner::run(interaction, &config);
 
-        let result = single_runner::run(interaction, &config);
+        reporter.interaction_finished(index, &outcome);
+        limit.record(&outcome);
+        outcomes.push(outcome);
+    }
 
-        print_over(result.to_icon().to_string());
+    outcomes
+}
 
-        if let Err(error) = result {
-            fails.push(error);
+/// The interactions not yet taken by a worker, in the order they are run
+struct WorkQueue<'a> {
+    interactions: &'a [InteractionTest],
+    next: AtomicUsize,
+}
+
+impl<'a> WorkQueue<'a> {
+    fn new(interactions: &'a [InteractionTest]) -> Self {
+        Self {
+            interactions,
+            next: AtomicUsize::new(0),
         }
-        println!();
     }
 
-    fails
+    /// Takes the next interaction and its index, `None` once all interactions were taken or the
+    /// run was stopped
+    fn take(&self) -> Option<(usize, &'a InteractionTest)> {
+        if cancel::should_stop() {
+            return None;
+        }
+
+        let index = self.next.fetch_add(1, Ordering::Relaxed);
+        self.interactions
+            .get(index)
+            .map(|interaction| (index, interaction))
+    }
+}
+
+/// Sent from the workers to the thread calling the reporter
+enum WorkerEvent {
+    Started(usize),
+    Finished(usize, Box<InteractionOutcome>),
 }
 
+/// Runs the interactions on `thread_count` workers that each take the next interaction from a
+/// shared queue once they are done with the previous one
 fn run_parallel(
     interactions: Vec<InteractionTest>,
     config: RunnerConfig,
     thread_count: u64,
-) -> Vec<RunnerError> {
-    let mut results: InteractionResultVec = interactions.iter().map(|_| None).collect();
-
-    let (start_tx, start_rx) = mpsc::channel();
-    let (result_tx, result_rx) = mpsc::channel();
-
-    let runner_thread = thread::spawn(move || {
-        let result_tx = result_tx;
-        let interactions = interactions;
-        let mut threads = Vec::new();
-
-        let mut i = 0;
-
-        loop {
-            if i >= interactions.len() {
-                break;
-            }
-
-            start_rx.recv().unwrap();
-
-            let interaction = interactions.get(i).unwrap().to_owned();
-            let config = config.clone();
-            let tx = result_tx.clone();
-
-            let handle = thread::spawn(move || {
-                let i = i;
-                let result = single_runner::run(interaction, &config);
-                tx.send((i, result)).expect("could not send");
+    reporter: &mut dyn Reporter,
+) -> Vec<InteractionOutcome> {
+    let mut results: Vec<Option<InteractionOutcome>> = interactions.iter().map(|_| None).collect();
+    let queue = WorkQueue::new(&interactions);
+    let worker_count = (thread_count as usize).min(interactions.len());
+    let mut limit = FailureLimit::new(config.max_failures);
+
+    thread::scope(|scope| {
+        let (event_tx, event_rx) = mpsc::channel();
+
+        for _ in 0..worker_count {
+            let event_tx = event_tx.clone();
+            let (queue, config) = (&queue, &config);
+
+            scope.spawn(move || {
+                while let Some((index, interaction)) = queue.take() {
+                    // the receiver lives until all workers are done
+                    let _ = event_tx.send(WorkerEvent::Started(index));
+                    let outcome = single_runner::run(interaction.clone(), config);
+                    let _ = event_tx.send(WorkerEvent::Finished(index, Box::new(outcome)));
+                }
             });
-
-            i += 1;
-            threads.push(handle);
-        }
-        for thread in threads {
-            thread.join().unwrap();
-        }
-    });
-
-    print_flush(create_progress_bar(&results));
-
-    let mut is_done = update_is_done(&results);
-
-    let mut running_threads = 0;
-
-    while !is_done {
-        while running_threads < thread_count {
-            start_tx.send(()).unwrap();
-            running_threads += 1;
         }
 
-        let (index, result) = result_rx.recv().unwrap();
-
-        results[index] = Some(result);
-        running_threads -= 1;
-        print_over(create_progress_bar(&results));
-
-        is_done = update_is_done(&results);
-    }
-    println!();
-
-    runner_thread.join().unwrap();
-
-    let mut fails: Vec<RunnerError> = Vec::new();
-
-    for result in results {
-        if let Some(Err(error)) = result {
-            fails.push(error);
+        // the loop below ends once every worker dropped its sender
+        drop(event_tx);
+
+        for event in event_rx {
+            match event {
+                WorkerEvent::Started(index) => {
+                    reporter.interaction_started(index, &interactions[index]);
+                }
+                WorkerEvent::Finished(index, outcome) => {
+                    reporter.interaction_finished(index, &outcome);
+                    limit.record(&outcome);
+                    results[index] = Some(*outcome);
+                }
+            }
         }
-    }
+    });
 
-    fails
+    results.into_iter().flatten().collect()
 }
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use crate::parser::InteractionTest;
+
+use super::RunnerError;
+
+pub struct InteractionOutcome {
+    pub interaction: InteractionTest,
+    pub result: Result<(), RunnerError>,
+    /// The wall time it took to run the interaction
+    pub duration: Duration,
+    /// The inputs written to and the outputs read from the program
+    pub transcript: Vec<String>,
+    /// Everything the program printed to stderr
+    pub stderr: String,
+    /// `None` if the program was killed, terminated by a signal or could not be started
+    pub exit_code: Option<i32>,
+}
+
+/// The kind of result of an interaction, used for counts and machine readable output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Passed,
+    Failed,
+    Errored,
+    TimedOut,
+    Cancelled,
+}
+
+impl Status {
+    /// Every status, in the order they are listed in summaries
+    pub const ALL: [Status; 5] = [
+        Status::Passed,
+        Status::Failed,
+        Status::Errored,
+        Status::TimedOut,
+        Status::Cancelled,
+    ];
+
+    /// The name used in machine readable output
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Passed => "passed",
+            Status::Failed => "failed",
+            Status::Errored => "errored",
+            Status::TimedOut => "timed_out",
+            Status::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl InteractionOutcome {
+    pub fn status(&self) -> Status {
+        match self.result {
+            Ok(_) => Status::Passed,
+            Err(RunnerError::Fail { .. } | RunnerError::ExitCode { .. }) => Status::Failed,
+            Err(RunnerError::Error { .. } | RunnerError::Start { .. } | RunnerError::Crash { .. }) => {
+                Status::Errored
+            }
+            Err(RunnerError::Timeout { .. }) => Status::TimedOut,
+            Err(RunnerError::Cancelled { .. }) => Status::Cancelled,
+        }
+    }
+}
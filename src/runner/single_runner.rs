@@ -1,188 +1,424 @@
-use std::{path::Path, process::{Command, Stdio, ChildStdout}, thread::{JoinHandle, self}, io::{Read, Write}, time::Duration, sync::mpsc};
-
-use regex::Regex;
+use std::{path::Path, process::{Child, Command, ExitStatus, Stdio}, thread::{JoinHandle, self}, io::{BufRead, BufReader, Read, Write}, time::{Duration, Instant}, sync::{mpsc::{self, Receiver}, Arc, Mutex}};
 
 use crate::parser::{InteractionTest, InteractionLineKind};
 
-use super::{RunnerConfig, RunnerError};
+use super::{cancel, error::{Mismatch, Transcript}, InteractionOutcome, RunnerConfig, RunnerError};
+
+/// How many lines are read after the last expected line when continuing after a mismatch
+const MAX_TRAILING_LINES: usize = 1000;
 
+pub fn run(interaction: InteractionTest, config: &RunnerConfig) -> InteractionOutcome {
+    let start = Instant::now();
+    let (result, transcript, stderr, exit_code) = run_interaction(interaction.clone(), config);
 
+    InteractionOutcome {
+      interaction,
+      result,
+      duration: start.elapsed(),
+      transcript,
+      stderr,
+      exit_code,
+    }
+}
 
-pub fn run(interaction: InteractionTest, config: &RunnerConfig) -> Result<(), RunnerError> {
+/// Runs the interaction and returns the result, the conversation with the program, everything it
+/// printed to stderr and its exit code
+fn run_interaction(
+  interaction: InteractionTest,
+  config: &RunnerConfig,
+) -> (Result<(), RunnerError>, Vec<String>, String, Option<i32>) {
   
-    let current_dir = Path::new(&interaction.file_path).parent().unwrap().to_string_lossy().to_string();
+    let current_dir = match &config.working_dir {
+      Some(dir) => dir.to_owned(),
+      None => Path::new(&interaction.file_path).parent().unwrap().to_path_buf(),
+    };
   
-    let mut child = Command::new(&config.command)
+    let mut command = Command::new(&config.command);
+    command
       .current_dir(current_dir)
       .stdin(Stdio::piped())
       .stdout(Stdio::piped())
       .stderr(Stdio::piped())
       .args(&config.arguments)
       .args(&interaction.command_arguments)
-      .spawn()
-      .expect("programm failed to start");
+      .envs(config.env.iter().cloned());
+
+    // a new process group, so every process the program starts can be killed with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let start = Instant::now();
+    let mut child = match command.spawn() {
+      Ok(child) => child,
+      Err(e) => {
+        let error = RunnerError::Start {
+          interaction,
+          error_message: format!("could not start '{}': {}", config.command, e),
+        };
+        return (Err(error), Vec::new(), String::new(), None);
+      }
+    };
   
-    let mut stdin = child.stdin.take().expect("failed to get stdin");
-    let mut stdout = child.stdout.take().expect("failed to get stdout");
+    let stdin = child.stdin.take().expect("failed to get stdin");
+    let stdout = child.stdout.take().expect("failed to get stdout");
+    let stderr = child.stderr.take().expect("failed to get stderr");
+
+    // both streams are read continuously, otherwise the program blocks once a pipe is full. The
+    // whole stderr is returned for reports, stdout is only needed line by line
+    let deadline = start + config.total_timeout;
+    let (mut stdout, _) = Lines::spawn(stdout, deadline);
+    let (mut stderr, stderr_output) = Lines::spawn(stderr, deadline);
   
     let timeout_move = config.timeout.to_owned();
   
-    let inout_thread: JoinHandle<Result<(), RunnerError>> = thread::spawn(move || {
+    let (diff, collect_all) = (config.diff, config.collect_all);
+
+    // the interaction is moved into the inout thread but needed for errors found after the
+    // program exited
+    let expected_exit_code = interaction.exit_code;
+    let exit_interaction = interaction.clone();
+    let continue_on_mismatch = diff || collect_all;
+    // notifies the main thread of a mismatch, so a program still waiting for input is not run
+    // until the total timeout
+    let (fail_sender, fail_receiver) = mpsc::channel();
+
+    let inout_thread: JoinHandle<(Result<(), RunnerError>, Vec<String>)> = thread::spawn(move || {
       let timeout = timeout_move;
-      let interaction_lines = interaction.lines.clone();
-      let mut line_index = 0;
+      // dropped to close stdin
+      let mut stdin = Some(stdin);
       let mut prev_lines = Vec::new();
-  
-      while let Some(inter_line) = &interaction_lines.get(line_index) {
-        line_index += 1;
-  
-        match inter_line.kind {
+      let mut transcript = Transcript::default();
+      let mut mismatches: Vec<Mismatch> = Vec::new();
+      // the output before the first mismatch
+      let mut fail_prev_output = None;
+
+      for inter_line in &interaction.lines {
+        let found = match &inter_line.kind {
           InteractionLineKind::Input => {
             let mut line = inter_line.content.to_string();
             line.push('\n');
-            stdin
-              .write(line.as_bytes())
-              .expect("could not write to stdin");
+            // the program might already have exited, which is reported by the next output line
+            if let Some(stdin) = &mut stdin {
+              let _ = stdin.write_all(line.as_bytes());
+            }
+
             let mut out_line = "> ".to_string();
             out_line += inter_line.content.as_str();
-            prev_lines.push(out_line);
+            prev_lines.push(out_line.clone());
+            transcript.expected.push(out_line.clone());
+            transcript.actual.push(out_line);
+            continue;
           }
-          InteractionLineKind::OutputLiteral => {
-            let result = read_line(stdout, &timeout);
-  
-            if let Some((line, out)) = result {
-              stdout = out;
-              if line == inter_line.content {
-                prev_lines.push(line);
-                continue;
-              }
-  
-              return Err(RunnerError::Fail {
-                interaction,
-                line: inter_line.line_idx,
-                expected: inter_line.content.to_string(),
-                found: line.to_string(),
-                prev_output: prev_lines,
-              });
-            }
-  
-            return Err(RunnerError::Fail {
-              interaction,
-              line: inter_line.line_idx,
-              expected: inter_line.content.to_string(),
-              found: "<timeout>".to_string(),
-              prev_output: prev_lines,
-            });
+          InteractionLineKind::CloseInput => {
+            stdin = None;
+
+            let out_line = "$eof".to_string();
+            prev_lines.push(out_line.clone());
+            transcript.expected.push(out_line.clone());
+            transcript.actual.push(out_line);
+            continue;
           }
-          InteractionLineKind::OutputRegex => {
-            let result = read_line(stdout, &timeout);
-  
-            if let Some((line, out)) = result {
-              stdout = out;
-              if Regex::new(&inter_line.content)
-                .unwrap()
-                .is_match(line.as_str())
-              {
-                prev_lines.push(line);
-                continue;
-              }
-  
-              return Err(RunnerError::Fail {
-                interaction,
-                line: inter_line.line_idx,
-                expected: inter_line.content.to_string(),
-                found: line.to_string(),
-                prev_output: prev_lines,
-              });
-            }
-  
-            return Err(RunnerError::Fail {
-              interaction,
-              line: inter_line.line_idx,
-              expected: inter_line.content.to_string(),
-              found: "<timeout>".to_string(),
-              prev_output: prev_lines,
-            });
+          InteractionLineKind::OutputLiteral | InteractionLineKind::OutputRegex(_) => {
+            stdout.next(timeout)
+          }
+          InteractionLineKind::ErrorLiteral | InteractionLineKind::ErrorRegex(_) => {
+            stderr.next(timeout)
+          }
+        };
+
+        let matches = match (&inter_line.kind, &found) {
+          (InteractionLineKind::OutputLiteral | InteractionLineKind::ErrorLiteral, Some(line)) => {
+            *line == inter_line.content
+          }
+          (InteractionLineKind::OutputRegex(regex) | InteractionLineKind::ErrorRegex(regex), Some(line)) => {
+            regex.is_match(line)
           }
+          _ => false,
+        };
+
+        // lines on stderr are marked like in the interaction file
+        let prefix = if inter_line.kind.is_stderr() { "<e " } else { "" };
+
+        // a matching regex line is shown as the line it matched, so it does not show up in diffs
+        transcript.expected.push(match (matches, &found) {
+          (true, Some(line)) => format!("{}{}", prefix, line),
+          _ => format!("{}{}", prefix, inter_line.content),
+        });
+        if let Some(line) = &found {
+          transcript.actual.push(format!("{}{}", prefix, line));
         }
-      }
-  
-      if let Some((line, _)) = read_line(stdout, &timeout) {
-        return Err(RunnerError::Fail {
-          interaction,
-          line: 0,
-          expected: "<EOF>".to_string(),
-          found: line,
-          prev_output: prev_lines,
+
+        if matches {
+          prev_lines.push(format!("{}{}", prefix, found.unwrap()));
+          continue;
+        }
+
+        if fail_prev_output.is_none() {
+          fail_prev_output = Some(prev_lines.clone());
+        }
+        mismatches.push(Mismatch {
+          line: inter_line.line_idx,
+          expected: inter_line.content.to_string(),
+          found: found.clone().unwrap_or_else(|| "<timeout>".to_string()),
         });
+
+        if !continue_on_mismatch {
+          break;
+        }
+
+        if let Some(line) = found {
+          prev_lines.push(format!("{}{}", prefix, line));
+        }
       }
-      return Ok(());
-    });
-  
-    let child_result = child.wait().expect("could not wait for child");
-  
-    let result = inout_thread.join().expect("could not join inout_thread");
-  
-    if !child_result.success() {
-      let mut stderr = child.stderr.take().expect("Failed to get stderr");
-      let mut error_message = String::new();
-      stderr
-        .read_to_string(&mut error_message)
-        .expect("could not read from stderr");
-  
-      if error_message.contains("java.util.NoSuchElementException")
-        && error_message.contains("java.util.Scanner")
-      {
-      } else {
-        if let Err(result) = result {
-          if let RunnerError::Fail {
-            interaction,
-            line,
-            expected: _,
-            found: _,
-            prev_output,
-          } = result
-          {
-            return Err(RunnerError::Error {
-              interaction,
-              line,
-              error_message,
-              error_code: child_result.code().unwrap(),
-              prev_output,
+
+      if mismatches.is_empty() || continue_on_mismatch {
+        let eof_line = interaction.lines.last().map(|l| l.line_idx + 1).unwrap_or(0);
+        let mut has_trailing_output = false;
+
+        let max_lines = if continue_on_mismatch { MAX_TRAILING_LINES } else { 1 };
+
+        for _ in 0..max_lines {
+          let Some(line) = stdout.next(timeout) else {
+            break;
+          };
+
+          transcript.actual.push(line.to_string());
+
+          if !has_trailing_output {
+            has_trailing_output = true;
+            fail_prev_output.get_or_insert_with(|| prev_lines.clone());
+            mismatches.push(Mismatch {
+              line: eof_line,
+              expected: "<EOF>".to_string(),
+              found: line,
             });
           }
         }
-        panic!("child had error while thread did not");
       }
-    }
+
+      let actual = transcript.actual.clone();
+
+      if mismatches.is_empty() {
+        return (Ok(()), actual);
+      }
+
+      if !collect_all {
+        mismatches.truncate(1);
+      }
+
+      let first = &mismatches[0];
+      let fail = RunnerError::Fail {
+        line: first.line,
+        expected: first.expected.to_string(),
+        found: first.found.to_string(),
+        interaction,
+        prev_output: fail_prev_output.unwrap_or_default(),
+        mismatches,
+        transcript: diff.then_some(transcript),
+      };
+
+      let _ = fail_sender.send(());
+      (Err(fail), actual)
+    });
+
+    let exit = wait_until(&mut child, deadline, &fail_receiver, config.timeout);
+    let elapsed = start.elapsed();
+
+    // killing the program closes its output, so both threads finish right away
+    let (result, transcript) = inout_thread.join().expect("could not join inout_thread");
+    let error_message = stderr_output.wait(config.timeout);
+
+    let child_result = match exit {
+      Exit::Exited(status) => status,
+      // the mismatch is reported, not that the program had to be killed
+      Exit::KilledAfterFail => return (result, transcript, error_message, None),
+      Exit::TimedOut => {
+        let error = RunnerError::Timeout {
+          interaction: exit_interaction,
+          elapsed,
+          prev_output: transcript.clone(),
+        };
+        return (Err(error), transcript, error_message, None);
+      }
+      Exit::Cancelled => {
+        let error = RunnerError::Cancelled {
+          interaction: exit_interaction,
+          elapsed,
+          prev_output: transcript.clone(),
+        };
+        return (Err(error), transcript, error_message, None);
+      }
+    };
+    let exit_code = child_result.code();
   
-    return result;
+    let exit_code_matches = match expected_exit_code {
+      Some(expected) => exit_code == Some(expected.code),
+      None => child_result.success(),
+    };
+
+    if exit_code_matches {
+      return (result, transcript, error_message, exit_code);
+    }
+
+    let end_line = exit_interaction.lines.last().map(|l| l.line_idx + 1).unwrap_or(0);
+
+    // a wrong output is reported instead of a wrong exit code
+    if let (Some(expected), Ok(())) = (expected_exit_code, &result) {
+      let error = RunnerError::ExitCode {
+        interaction: exit_interaction,
+        line: expected.line_idx,
+        expected: expected.code,
+        found: exit_code,
+        error_message: error_message.to_string(),
+        prev_output: transcript.clone(),
+      };
+      return (Err(error), transcript, error_message, exit_code);
+    }
+
+    if child_result.success() || config.ignored_crashes.iter().any(|r| r.is_match(&error_message)) {
+      return (result, transcript, error_message, exit_code);
+    }
+
+    let error = match result {
+      Err(RunnerError::Fail {
+        interaction,
+        line,
+        prev_output,
+        ..
+      }) => RunnerError::Error {
+        interaction,
+        line,
+        error_message: error_message.to_string(),
+        error_code: exit_code,
+        prev_output,
+      },
+      Ok(()) => RunnerError::Crash {
+        interaction: exit_interaction,
+        line: end_line,
+        error_message: error_message.to_string(),
+        error_code: exit_code,
+        prev_output: transcript.clone(),
+      },
+      Err(error) => error,
+    };
+
+    (Err(error), transcript, error_message, exit_code)
   }
   
-  fn read_line(mut stdout: ChildStdout, timeout: &Duration) -> Option<(String, ChildStdout)> {
-    let (tx, rx) = mpsc::channel();
-  
-    thread::spawn(move || {
-      let mut line_buffer = Vec::new();
-      let mut buf: [u8; 1] = [0];
-      loop {
-        let result = stdout.read_exact(&mut buf);
-        if let Err(_) = result {
-          return;
-        }
-        if buf[0] == ('\n' as u8) {
-          break;
+  /// How the program stopped
+  enum Exit {
+    Exited(ExitStatus),
+    /// Killed because it was still running at the deadline
+    TimedOut,
+    /// Killed because it was still running after its output mismatched
+    KilledAfterFail,
+    /// Killed because the run was cancelled
+    Cancelled,
+  }
+
+  /// Waits for the program to exit. Once `deadline` passed or the run was cancelled, the program
+  /// is killed. Every process it started is killed in any case. After a mismatch was sent over `failed`,
+  /// the program only gets `grace` to exit, so a crash at the mismatch is still reported.
+  fn wait_until(child: &mut Child, mut deadline: Instant, failed: &Receiver<()>, grace: Duration) -> Exit {
+    let mut has_failed = false;
+
+    loop {
+      if let Some(status) = child.try_wait().expect("could not wait for child") {
+        // processes the program started in the background would keep running and keep its
+        // output open
+        kill(child);
+        return Exit::Exited(status);
+      }
+
+      if !has_failed && failed.try_recv().is_ok() {
+        has_failed = true;
+        deadline = deadline.min(Instant::now() + grace);
+      }
+
+      let exit = if cancel::should_stop() {
+        Exit::Cancelled
+      } else if Instant::now() >= deadline {
+        if has_failed { Exit::KilledAfterFail } else { Exit::TimedOut }
+      } else {
+        thread::sleep(Duration::from_millis(10));
+        continue;
+      };
+
+      kill(child);
+      let _ = child.wait();
+      return exit;
+    }
+  }
+
+  #[cfg(unix)]
+  fn kill(child: &mut Child) {
+    // the program is the leader of its process group, a negative pid kills the whole group
+    unsafe {
+      libc::kill(-(child.id() as i32), libc::SIGKILL);
+    }
+  }
+
+  #[cfg(not(unix))]
+  fn kill(child: &mut Child) {
+    let _ = child.kill();
+  }
+
+  /// The lines of stdout or stderr of the program, read by a separate thread
+  struct Lines {
+    receiver: Receiver<String>,
+    /// No line is waited for after this, so many missing lines do not exceed the total timeout
+    deadline: Instant,
+  }
+
+  impl Lines {
+    /// Starts a thread reading `stream` until it is closed, everything it read is kept in the
+    /// returned `Output`
+    fn spawn<R: Read + Send + 'static>(stream: R, deadline: Instant) -> (Self, Output) {
+      let (sender, receiver) = mpsc::channel();
+      let text = Arc::new(Mutex::new(String::new()));
+      let output = Arc::clone(&text);
+
+      let thread = thread::spawn(move || {
+        let mut stream = BufReader::new(stream);
+        let mut buffer = Vec::new();
+        while let Ok(1..) = stream.read_until(b'\n', &mut buffer) {
+          let line = String::from_utf8_lossy(&buffer).to_string();
+          *output.lock().unwrap() += &line;
+          // the receiver is gone once all lines were checked
+          let _ = sender.send(line.trim_end_matches(['\n', '\r']).to_string());
+          buffer.clear();
         }
-        line_buffer.push(buf[0]);
+      });
+
+      let lines = Self { receiver, deadline };
+
+      (lines, Output { thread, text })
+    }
+
+    /// The next line, or `None` if the stream is closed or no line was read within `timeout` or
+    /// before the deadline
+    fn next(&mut self, timeout: Duration) -> Option<String> {
+      let remaining = self.deadline.saturating_duration_since(Instant::now());
+
+      self.receiver.recv_timeout(timeout.min(remaining)).ok()
+    }
+  }
+
+  /// Everything the thread of `Lines` read from its stream
+  struct Output {
+    thread: JoinHandle<()>,
+    text: Arc<Mutex<String>>,
+  }
+
+  impl Output {
+    /// Waits up to `limit` for the stream to be closed and returns everything read. A process
+    /// that left the process group of the program can keep the stream open forever
+    fn wait(self, limit: Duration) -> String {
+      let end = Instant::now() + limit;
+      while !self.thread.is_finished() && Instant::now() < end {
+        thread::sleep(Duration::from_millis(10));
       }
-  
-      let string = String::from_utf8_lossy(&line_buffer).to_string();
-  
-      let _ = tx.send((string, stdout));
-    });
-  
-    let received = rx.recv_timeout(timeout.to_owned()).ok();
-  
-    received
+
+      self.text.lock().unwrap().clone()
+    }
   }
-  
\ No newline at end of file
@@ -0,0 +1,51 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static COLORS: AtomicBool = AtomicBool::new(true);
+
+/// Enables or disables the ANSI color codes of all `style_*` functions
+pub fn set_colors(enabled: bool) {
+    COLORS.store(enabled, Ordering::Relaxed);
+}
+
+fn style<D: Display>(message: D, code: &str) -> String {
+    if COLORS.load(Ordering::Relaxed) {
+        format!("\x1b[{}m{}\x1b[0m", code, message)
+    } else {
+        message.to_string()
+    }
+}
+
+pub fn style_fail(message: &str) -> String {
+    style(message, "1;91")
+}
+
+pub fn style_error(message: &str) -> String {
+    style(message, "1;30;41")
+}
+
+pub fn style_bold<D: Display>(message: D) -> String {
+    style(message, "1")
+}
+
+pub fn style_green<D: Display>(message: D) -> String {
+    style(message, "32")
+}
+
+pub fn style_red<D: Display>(message: D) -> String {
+    style(message, "91")
+}
+
+pub fn style_blue<D: Display>(message: D) -> String {
+    style(message, "1;94")
+}
+
+pub fn style_red_emphasized(message: &str) -> String {
+    style(message, "7;91")
+}
+
+pub fn style_green_emphasized(message: &str) -> String {
+    style(message, "7;32")
+}
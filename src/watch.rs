@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeSet,
+    env::current_dir,
+    fs,
+    io::{stdout, IsTerminal},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+
+use crate::{
+    base_dir,
+    cli::{Cli, OutputFormat},
+    collect::{interaction_file_regex, parse_interactions},
+    config::OcdConfig,
+    filter::InteractionFilter,
+    load_config, run, run_interactions, runner,
+};
+
+/// How long to wait for more changes after the first one before rerunning
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+enum Change {
+    /// The config file changed, everything needs to be reloaded
+    Config,
+    /// The program changed, all interactions need to run again
+    Program,
+    /// An interaction file changed, only it needs to run again
+    Interaction(PathBuf),
+}
+
+enum Rerun {
+    All,
+    Interactions(BTreeSet<PathBuf>),
+}
+
+/// The paths watched for changes, all of them canonicalized
+struct WatchedPaths {
+    config: PathBuf,
+    interactions: PathBuf,
+    interaction_regex: Regex,
+    /// Files or directories containing the program
+    program: Vec<PathBuf>,
+}
+
+impl WatchedPaths {
+    fn new(cli: &Cli, config: &OcdConfig) -> Result<Self, String> {
+        let base_dir = base_dir(&cli.config);
+
+        let mut program = Vec::new();
+        if let Some(class_path) = &config.class_path {
+            program.push(canonicalize(&base_dir.join(class_path))?);
+        }
+        if let Some(program_config) = &config.program {
+            // commands like `python3` are not files that can be watched
+            let command = base_dir.join(&program_config.command);
+            if command.is_file() {
+                program.push(canonicalize(&command)?);
+            }
+
+            // scripts like `main.py` in `python3 main.py` are given as arguments. Without a working
+            // directory they are looked up next to the config and in the interaction directory
+            let dirs = match &program_config.working_dir {
+                Some(dir) => vec![base_dir.join(dir)],
+                None => vec![base_dir.clone(), base_dir.join(&config.interaction.path)],
+            };
+            for arg in program_config.args.iter().flatten() {
+                for dir in &dirs {
+                    let file = dir.join(arg);
+                    if file.is_file() {
+                        let file = canonicalize(&file)?;
+                        if !program.contains(&file) {
+                            program.push(file);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(WatchedPaths {
+            config: canonicalize(&cli.config)?,
+            interactions: canonicalize(&base_dir.join(&config.interaction.path))?,
+            interaction_regex: interaction_file_regex(&config.interaction.pattern)?,
+            program,
+        })
+    }
+
+    fn watch(&self, watcher: &mut RecommendedWatcher) -> Result<(), String> {
+        // the parent directory is watched, editors often replace files instead of writing them
+        let config_dir = self.config.parent().unwrap_or(Path::new("/"));
+        let mut paths = vec![(config_dir, RecursiveMode::NonRecursive)];
+
+        paths.push((&self.interactions, RecursiveMode::Recursive));
+
+        for path in &self.program {
+            if path.is_dir() {
+                paths.push((path, RecursiveMode::Recursive));
+            } else {
+                paths.push((path.parent().unwrap(), RecursiveMode::NonRecursive));
+            }
+        }
+
+        for (path, mode) in paths {
+            watcher
+                .watch(path, mode)
+                .map_err(|e| format!("could not watch '{}': {}", path.display(), e))?;
+        }
+
+        Ok(())
+    }
+
+    fn classify(&self, path: &Path) -> Option<Change> {
+        if path == self.config {
+            return Some(Change::Config);
+        }
+
+        if self.program.iter().any(|p| path.starts_with(p)) {
+            return Some(Change::Program);
+        }
+
+        if path.starts_with(&self.interactions) {
+            let file_name = path.file_name()?.to_str()?;
+            if self.interaction_regex.is_match(file_name) {
+                return Some(Change::Interaction(path.to_path_buf()));
+            }
+        }
+
+        None
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|e| format!("could not find '{}': {}", path.display(), e))
+}
+
+/// Shortens `path` to be relative to the current directory, like the paths of a normal run
+fn display_path(path: &Path) -> String {
+    let relative = current_dir()
+        .and_then(fs::canonicalize)
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf));
+
+    relative
+        .unwrap_or_else(|| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Blocks until relevant changes happened and returns what needs to run again
+fn wait_for_changes(rx: &Receiver<notify::Result<Event>>, watched: &WatchedPaths) -> Rerun {
+    let mut changed_interactions: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut rerun_all = false;
+
+    let mut timeout = None;
+
+    loop {
+        let event = match timeout {
+            None => rx.recv().ok(),
+            Some(timeout) => rx.recv_timeout(timeout).ok(),
+        };
+
+        let Some(event) = event else {
+            if rerun_all {
+                return Rerun::All;
+            }
+
+            let existing: BTreeSet<PathBuf> =
+                changed_interactions.iter().filter(|p| p.is_file()).cloned().collect();
+            if !existing.is_empty() {
+                return Rerun::Interactions(existing);
+            }
+
+            // only deleted interactions, nothing to run
+            changed_interactions.clear();
+            timeout = None;
+            continue;
+        };
+
+        let Ok(event) = event else {
+            continue;
+        };
+
+        // reading files (e.g. the JVM loading classes) must not trigger a rerun
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            continue;
+        }
+
+        for path in &event.paths {
+            match watched.classify(path) {
+                Some(Change::Config) | Some(Change::Program) => rerun_all = true,
+                Some(Change::Interaction(path)) => {
+                    changed_interactions.insert(path);
+                }
+                None => continue,
+            }
+            timeout = Some(DEBOUNCE);
+        }
+    }
+}
+
+type Watching = (
+    OcdConfig,
+    WatchedPaths,
+    RecommendedWatcher,
+    Receiver<notify::Result<Event>>,
+);
+
+fn start_watching(cli: &Cli) -> Result<Watching, String> {
+    let config = load_config(cli)?;
+    let watched = WatchedPaths::new(cli, &config)?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("could not watch: {}", e))?;
+    watched.watch(&mut watcher)?;
+
+    Ok((config, watched, watcher, rx))
+}
+
+fn clear_screen(cli: &Cli) {
+    if cli.format == OutputFormat::Text && stdout().is_terminal() {
+        print!("\x1b[2J\x1b[H");
+    }
+}
+
+/// Runs all interactions and reruns them whenever the program, the config or an interaction file
+/// changes. Only changed interaction files are rerun if nothing else changed.
+pub fn watch(cli: &Cli) -> Result<u8, String> {
+    let mut rerun = Rerun::All;
+    let mut current = None;
+
+    loop {
+        match start_watching(cli) {
+            Ok(watching) => current = Some(watching),
+            // keep watching with the previous config until the config is fixed
+            Err(e) if current.is_some() => eprintln!("{}", e),
+            Err(e) => return Err(e),
+        }
+        let (config, watched, _watcher, rx) = current.as_ref().unwrap();
+
+        loop {
+            clear_screen(cli);
+
+            let result = match &rerun {
+                Rerun::All => run(cli),
+                Rerun::Interactions(paths) => {
+                    let file_paths = paths.iter().map(|p| display_path(p)).collect();
+                    InteractionFilter::new(cli).and_then(|filter| {
+                        let (interactions, parse_errors) = parse_interactions(&filter, file_paths);
+                        run_interactions(cli, config, interactions, parse_errors)
+                    })
+                }
+            };
+
+            match result {
+                Ok(code) if runner::is_cancelled() => return Ok(code),
+                Ok(_) => {}
+                Err(e) => eprintln!("{}", e),
+            }
+
+            if cli.format == OutputFormat::Text {
+                println!("Waiting for changes...");
+            }
+
+            rerun = wait_for_changes(rx, watched);
+
+            if matches!(rerun, Rerun::All) {
+                // the config might have changed, reload it and everything watched
+                break;
+            }
+        }
+    }
+}